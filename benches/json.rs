@@ -0,0 +1,98 @@
+//! pest vs winnow on a small document, a large generated one and a deeply
+//! nested one, reported in bytes per second.
+//!
+//! ```bash
+//! cargo bench --bench json
+//! ```
+
+use std::{fmt::Write, thread};
+
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion, Throughput};
+use grammar::json::{ParseLimits, ParseOptions, BACKENDS};
+
+/// Nesting levels of the deep document, well past the default
+/// [`ParseLimits::max_depth`].
+const DEEP: usize = 1000;
+
+const SMALL: &str = r#"{
+  "name": "John Doe",
+  "age": 43,
+  "is_student": false,
+  "marks": [90, 80.5, 85],
+  "address": {"city": "London", "zip": "E1 6AN"},
+  "phones": ["+44 1234567", "+44 2345678"]
+}"#;
+
+/// An array of `n` records mixing every kind of value, about 200 bytes each.
+fn large(n: usize) -> String {
+    let mut s = String::from("[\n");
+    for i in 0..n {
+        if i > 0 {
+            s.push_str(",\n");
+        }
+        write!(
+            s,
+            r#"  {{"id": {i}, "name": "user \"{i}\"\té", "score": {score}, "active": {active}, "tags": ["a", "b{i}", null], "location": {{"lat": {lat}, "lng": -{lat}e-1}}}}"#,
+            score = i as f64 / 7.0,
+            active = i % 2 == 0,
+            lat = i % 90,
+        )
+        .unwrap();
+    }
+    s.push_str("\n]");
+    s
+}
+
+/// Arrays and objects alternating `depth` levels deep.
+fn deep(depth: usize) -> String {
+    let mut s = String::new();
+    for i in 0..depth {
+        s.push_str(if i % 2 == 0 { "[1, " } else { r#"{"a": "# });
+    }
+    s.push_str("null");
+    for i in (0..depth).rev() {
+        s.push(if i % 2 == 0 { ']' } else { '}' });
+    }
+    s
+}
+
+fn bench_json(c: &mut Criterion) {
+    let options = ParseOptions {
+        limits: ParseLimits {
+            max_depth: DEEP,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let documents = [
+        ("small", SMALL.to_string()),
+        ("large", large(5_000)),
+        ("deep", deep(DEEP)),
+    ];
+
+    let mut group = c.benchmark_group("json");
+    for (name, input) in &documents {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        for backend in BACKENDS {
+            group.bench_with_input(BenchmarkId::new(backend.name(), name), input, |b, input| {
+                b.iter(|| backend.parse_with(black_box(input), &options).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_json);
+
+// `criterion_main!`, but on a thread with room for the deep document
+fn main() {
+    thread::Builder::new()
+        .stack_size(256 << 20)
+        .spawn(|| {
+            benches();
+            Criterion::default().configure_from_args().final_summary();
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
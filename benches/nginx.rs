@@ -0,0 +1,80 @@
+//! regex vs winnow on a generated access log, reported in lines per second.
+//!
+//! ```bash
+//! cargo bench --bench nginx
+//! ```
+
+use std::fmt::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use grammar::nginx::{self, NginxError, NginxLog};
+
+const LINES: usize = 20_000;
+
+type Parse = fn(&str) -> Result<NginxLog, NginxError>;
+
+/// `n` log lines in the combined format, varied with a fixed-seed generator
+/// so every run parses the same input.
+fn generate(n: usize) -> String {
+    const METHODS: [&str; 4] = ["GET", "POST", "PUT", "DELETE"];
+    const STATUSES: [u16; 5] = [200, 200, 304, 404, 500];
+    const AGENTS: [&str; 3] = [
+        "Debian APT-HTTP/1.3 (0.8.16~exp12ubuntu10.21)",
+        "Mozilla/5.0 (X11; Linux x86_64; rv:126.0) Gecko/20100101 Firefox/126.0",
+        "curl/8.7.1",
+    ];
+
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    let mut s = String::new();
+    for _ in 0..n {
+        let r = next();
+        writeln!(
+            s,
+            r#"{}.{}.{}.{} - - [17/May/2015:{:02}:{:02}:{:02} +0000] "{} /downloads/product_{} HTTP/1.1" {} {} "-" "{}""#,
+            r % 223 + 1,
+            (r >> 8) & 0xff,
+            (r >> 16) & 0xff,
+            (r >> 24) & 0xff,
+            (r >> 32) % 24,
+            (r >> 40) % 60,
+            (r >> 48) % 60,
+            METHODS[r as usize % METHODS.len()],
+            r % 100,
+            STATUSES[r as usize % STATUSES.len()],
+            r % 100_000,
+            AGENTS[r as usize % AGENTS.len()],
+        )
+        .unwrap();
+    }
+    s
+}
+
+fn bench_nginx(c: &mut Criterion) {
+    let input = generate(LINES);
+    let backends: [(&str, Parse); 2] = [
+        ("regex", nginx::regex::parse),
+        ("winnow", nginx::winnow::parse),
+    ];
+
+    let mut group = c.benchmark_group("nginx");
+    group.throughput(Throughput::Elements(LINES as u64));
+    for (name, parse) in backends {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for line in black_box(&input).lines() {
+                    parse(line).unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_nginx);
+criterion_main!(benches);
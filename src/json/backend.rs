@@ -0,0 +1,60 @@
+//! The parser implementations behind one interface, so callers can pick one
+//! at runtime and tests can hold them against each other.
+
+use super::{pest, winnow, JsonError, JsonValue, ParseOptions};
+
+/// A parser that turns a JSON document into the shared [`JsonValue`].
+///
+/// ```
+/// use grammar::json::{self, JsonValue};
+///
+/// for backend in json::BACKENDS {
+///     let v = backend.parse(r#"{"a": [1, 2.5]}"#)?;
+///     assert_eq!(v.pointer("/a/1")?, &JsonValue::Double(2.5), "{}", backend.name());
+/// }
+/// assert_eq!(json::backend("pest").map(|b| b.name()), Some("pest"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait JsonBackend: Sync {
+    fn name(&self) -> &'static str;
+
+    fn parse_with(&self, input: &str, options: &ParseOptions) -> Result<JsonValue, JsonError>;
+
+    fn parse(&self, input: &str) -> Result<JsonValue, JsonError> {
+        self.parse_with(input, &ParseOptions::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PestBackend;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WinnowBackend;
+
+/// Every backend, the default one last.
+pub const BACKENDS: [&dyn JsonBackend; 2] = [&PestBackend, &WinnowBackend];
+
+/// The backend called `name`, as returned by [`JsonBackend::name`].
+pub fn backend(name: &str) -> Option<&'static dyn JsonBackend> {
+    BACKENDS.into_iter().find(|backend| backend.name() == name)
+}
+
+impl JsonBackend for PestBackend {
+    fn name(&self) -> &'static str {
+        "pest"
+    }
+
+    fn parse_with(&self, input: &str, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+        pest::parse_with(input, options)
+    }
+}
+
+impl JsonBackend for WinnowBackend {
+    fn name(&self) -> &'static str {
+        "winnow"
+    }
+
+    fn parse_with(&self, input: &str, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+        winnow::parse_with(input, options)
+    }
+}
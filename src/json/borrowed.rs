@@ -0,0 +1,93 @@
+//! A [`JsonValue`] that borrows its strings from the input.
+//!
+//! Strings and keys without escape sequences are slices of the parsed text,
+//! only the ones that had to be decoded are allocated.
+
+use std::borrow::Cow;
+
+use indexmap::IndexMap;
+
+use super::{JsonError, ParseOptions};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue<'a> {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Double(f64),
+    Number(super::Number),
+    String(Cow<'a, str>),
+    Array(Vec<JsonValue<'a>>),
+    Object(JsonObject<'a>),
+}
+
+/// Object members in the order they appear in the document.
+pub type JsonObject<'a> = IndexMap<Cow<'a, str>, JsonValue<'a>>;
+
+impl JsonValue<'_> {
+    /// Copy every borrowed string, detaching the value from the input.
+    pub fn into_owned(self) -> super::JsonValue {
+        match self {
+            JsonValue::Null => super::JsonValue::Null,
+            JsonValue::Bool(v) => super::JsonValue::Bool(v),
+            JsonValue::Integer(v) => super::JsonValue::Integer(v),
+            JsonValue::Double(v) => super::JsonValue::Double(v),
+            JsonValue::Number(v) => super::JsonValue::Number(v),
+            JsonValue::String(v) => super::JsonValue::String(v.into_owned()),
+            JsonValue::Array(values) => {
+                super::JsonValue::Array(values.into_iter().map(JsonValue::into_owned).collect())
+            }
+            JsonValue::Object(obj) => super::JsonValue::Object(
+                obj.into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Parse a JSON document with the winnow backend, borrowing from `input`.
+pub fn parse(input: &str) -> Result<JsonValue<'_>, JsonError> {
+    super::winnow::parse_borrowed(input)
+}
+
+/// Parse a JSON document with the winnow backend and custom options,
+/// borrowing from `input`.
+pub fn parse_with<'a>(input: &'a str, options: &ParseOptions) -> Result<JsonValue<'a>, JsonError> {
+    super::winnow::parse_borrowed_with(input, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_borrowed_should_borrow_unescaped_strings() {
+        let input = r#"{"plain": "text", "esc\"aped": ["a\nb", "c"]}"#;
+        let JsonValue::Object(obj) = parse(input).unwrap() else {
+            panic!("expected object");
+        };
+        let keys: Vec<_> = obj.keys().collect();
+        assert!(matches!(keys[0], Cow::Borrowed("plain")));
+        assert!(matches!(keys[1], Cow::Owned(k) if k == "esc\"aped"));
+        assert!(matches!(
+            &obj["plain"],
+            JsonValue::String(Cow::Borrowed("text"))
+        ));
+
+        let JsonValue::Array(values) = &obj[1] else {
+            panic!("expected array");
+        };
+        assert!(matches!(&values[0], JsonValue::String(Cow::Owned(s)) if s == "a\nb"));
+        assert!(matches!(&values[1], JsonValue::String(Cow::Borrowed("c"))));
+    }
+
+    #[test]
+    fn into_owned_should_match_owned_parse() {
+        let input = r#"{"name": "J \"D\"", "marks": [90, -80.5, null, true], "a": {"b": "c"}}"#;
+        assert_eq!(
+            parse(input).unwrap().into_owned(),
+            crate::json::parse(input).unwrap()
+        );
+    }
+}
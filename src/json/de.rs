@@ -0,0 +1,415 @@
+use std::fmt;
+
+use indexmap::map;
+use serde::{
+    de::{
+        self, value::StrDeserializer, DeserializeOwned, DeserializeSeed, EnumAccess,
+        IntoDeserializer, MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+
+use super::{
+    winnow, DeserializeError, JsonError, JsonObject, JsonValue, Number, ParseError, ParseOptions,
+    PathSegment,
+};
+
+/// Deserialize a `T` from JSON text, parsed with the winnow backend. Values
+/// of the wrong type are reported with their path and position in `input`.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, JsonError> {
+    from_str_with(input, &ParseOptions::default())
+}
+
+pub fn from_str_with<T: DeserializeOwned>(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<T, JsonError> {
+    let value = winnow::parse_with(input, options)?;
+    T::deserialize(value).map_err(|e| match e {
+        JsonError::Deserialize(mut e) => {
+            if let Some(offset) = winnow::locate(input, options, &e.path) {
+                let message = format!("{} at `{}`", e.message, e.path_string());
+                e.location = Some(ParseError::new(input, offset).with_message(message));
+            }
+            JsonError::Deserialize(e)
+        }
+        e => e,
+    })
+}
+
+/// Deserialize a `T` from an already parsed value.
+pub fn from_value<T: DeserializeOwned>(value: JsonValue) -> Result<T, JsonError> {
+    T::deserialize(value)
+}
+
+impl de::Error for JsonError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeserializeError::new(msg.to_string()).into()
+    }
+}
+
+/// Prefix the path of a deserialization error with the step that led to it.
+fn at(e: JsonError, segment: PathSegment) -> JsonError {
+    match e {
+        JsonError::Deserialize(mut e) => {
+            e.path.insert(0, segment);
+            JsonError::Deserialize(e)
+        }
+        e => e,
+    }
+}
+
+impl JsonValue {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            JsonValue::Null => Unexpected::Unit,
+            JsonValue::Bool(v) => Unexpected::Bool(*v),
+            JsonValue::Integer(v) => Unexpected::Signed(*v),
+            JsonValue::Double(v) => Unexpected::Float(*v),
+            JsonValue::Number(v) => match (v.as_i64(), v.as_u64()) {
+                (Some(i), _) => Unexpected::Signed(i),
+                (_, Some(u)) => Unexpected::Unsigned(u),
+                _ => Unexpected::Float(v.as_f64()),
+            },
+            JsonValue::String(v) => Unexpected::Str(v),
+            JsonValue::Array(_) => Unexpected::Seq,
+            JsonValue::Object(_) => Unexpected::Map,
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for JsonValue {
+    type Error = JsonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        match self {
+            JsonValue::Null => visitor.visit_unit(),
+            JsonValue::Bool(v) => visitor.visit_bool(v),
+            JsonValue::Integer(v) => visitor.visit_i64(v),
+            JsonValue::Double(v) => visitor.visit_f64(v),
+            JsonValue::Number(v) => visit_number(v, visitor),
+            JsonValue::String(v) => visitor.visit_string(v),
+            JsonValue::Array(values) => visit_array(values, visitor),
+            JsonValue::Object(obj) => visit_object(obj, visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, JsonError> {
+        match self {
+            JsonValue::Null => visitor.visit_none(),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A unit variant is a string, any other variant an object with the
+    /// variant name as its only key.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        match self {
+            JsonValue::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            JsonValue::Object(obj) if obj.len() == 1 => {
+                let (variant, value) = obj.into_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            v => Err(de::Error::invalid_type(
+                v.unexpected(),
+                &"a string or an object with a single key",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, JsonError> for JsonValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// The narrowest integer type that holds the value, so `u64` and `i128`
+/// fields receive IDs beyond `i64` exactly; anything else as an `f64`.
+fn visit_number<'de, V: Visitor<'de>>(v: Number, visitor: V) -> Result<V::Value, JsonError> {
+    if let Some(i) = v.as_i64() {
+        return visitor.visit_i64(i);
+    }
+    if let Some(u) = v.as_u64() {
+        return visitor.visit_u64(u);
+    }
+    if v.is_integer() {
+        if let Ok(i) = v.as_str().parse() {
+            return visitor.visit_i128(i);
+        }
+        if let Ok(u) = v.as_str().parse() {
+            return visitor.visit_u128(u);
+        }
+    }
+    visitor.visit_f64(v.as_f64())
+}
+
+fn visit_array<'de, V: Visitor<'de>>(
+    values: Vec<JsonValue>,
+    visitor: V,
+) -> Result<V::Value, JsonError> {
+    let len = values.len();
+    let mut seq = SeqDeserializer {
+        iter: values.into_iter(),
+        index: 0,
+    };
+    let ret = visitor.visit_seq(&mut seq)?;
+    if seq.iter.len() > 0 {
+        return Err(de::Error::invalid_length(len, &"fewer elements in array"));
+    }
+    Ok(ret)
+}
+
+fn visit_object<'de, V: Visitor<'de>>(obj: JsonObject, visitor: V) -> Result<V::Value, JsonError> {
+    let len = obj.len();
+    let mut map = MapDeserializer {
+        iter: obj.into_iter(),
+        value: None,
+    };
+    let ret = visitor.visit_map(&mut map)?;
+    if map.iter.len() > 0 {
+        return Err(de::Error::invalid_length(len, &"fewer members in object"));
+    }
+    Ok(ret)
+}
+
+struct SeqDeserializer {
+    iter: std::vec::IntoIter<JsonValue>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = JsonError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, JsonError> {
+        let Some(value) = self.iter.next() else {
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(value)
+            .map(Some)
+            .map_err(|e| at(e, PathSegment::Index(index)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer {
+    iter: map::IntoIter<String, JsonValue>,
+    value: Option<(String, JsonValue)>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = JsonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, JsonError> {
+        let Some((key, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+        let ret = seed.deserialize(StrDeserializer::<JsonError>::new(&key))?;
+        self.value = Some((key, value));
+        Ok(Some(ret))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, JsonError> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| DeserializeError::new("value requested before key"))?;
+        seed.deserialize(value)
+            .map_err(|e| at(e, PathSegment::Key(key)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: JsonValue,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = JsonError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer), JsonError> {
+        let variant = seed.deserialize(StrDeserializer::<JsonError>::new(&self.variant))?;
+        let value = VariantDeserializer {
+            variant: self.variant,
+            value: self.value,
+        };
+        Ok((variant, value))
+    }
+}
+
+struct VariantDeserializer {
+    variant: String,
+    value: JsonValue,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = JsonError;
+
+    fn unit_variant(self) -> Result<(), JsonError> {
+        match self.value {
+            JsonValue::Null => Ok(()),
+            v => Err(at(
+                de::Error::invalid_type(v.unexpected(), &"unit variant"),
+                PathSegment::Key(self.variant),
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, JsonError> {
+        seed.deserialize(self.value)
+            .map_err(|e| at(e, PathSegment::Key(self.variant)))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        let ret = match self.value {
+            JsonValue::Array(values) => visit_array(values, visitor),
+            v => Err(de::Error::invalid_type(v.unexpected(), &"tuple variant")),
+        };
+        ret.map_err(|e| at(e, PathSegment::Key(self.variant)))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, JsonError> {
+        let ret = match self.value {
+            JsonValue::Object(obj) => visit_object(obj, visitor),
+            v => Err(de::Error::invalid_type(v.unexpected(), &"struct variant")),
+        };
+        ret.map_err(|e| at(e, PathSegment::Key(self.variant)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Student {
+        name: String,
+        age: u8,
+        marks: Vec<f64>,
+        address: Option<Address>,
+        grade: Grade,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Address {
+        city: String,
+        zip: u32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Grade {
+        Pass,
+        Fail { retry: bool },
+    }
+
+    #[test]
+    fn from_str_should_work() {
+        let input = r#"{
+            "name": "John Doe",
+            "age": 30,
+            "marks": [90, 80.5],
+            "address": {"city": "New York", "zip": 10001},
+            "grade": {"Fail": {"retry": true}}
+        }"#;
+        let student: Student = from_str(input).unwrap();
+        assert_eq!(
+            student,
+            Student {
+                name: "John Doe".into(),
+                age: 30,
+                marks: vec![90.0, 80.5],
+                address: Some(Address {
+                    city: "New York".into(),
+                    zip: 10001,
+                }),
+                grade: Grade::Fail { retry: true },
+            }
+        );
+
+        let input = r#"{"name": "A", "age": 1, "marks": [], "address": null, "grade": "Pass"}"#;
+        let student: Student = from_value(crate::json::parse(input).unwrap()).unwrap();
+        assert_eq!((student.address, student.grade), (None, Grade::Pass));
+    }
+
+    #[test]
+    fn from_str_should_report_path_and_position() {
+        let input = "{\"name\": \"A\", \"age\": 1, \"grade\": \"Pass\",\n \"marks\": [1, \"x\"]}";
+        let Err(JsonError::Deserialize(e)) = from_str::<Student>(input) else {
+            panic!("expected deserialize error");
+        };
+        assert_eq!(e.path_string(), ".marks[1]");
+        assert_eq!(e.message, r#"invalid type: string "x", expected f64"#);
+        let location = e.location.as_ref().unwrap();
+        assert_eq!((location.line, location.column), (2, 15));
+        assert!(e
+            .to_string()
+            .ends_with("2 |  \"marks\": [1, \"x\"]}\n  |               ^"));
+
+        let input = r#"{"name": "A", "age": 300, "marks": [], "grade": "Pass"}"#;
+        let Err(JsonError::Deserialize(e)) = from_str::<Student>(input) else {
+            panic!("expected deserialize error");
+        };
+        assert_eq!(e.path_string(), ".age");
+        assert_eq!(e.location.unwrap().column, 22);
+
+        let input = r#"{"name": "A", "marks": [], "grade": "Pass"}"#;
+        let Err(JsonError::Deserialize(e)) = from_str::<Student>(input) else {
+            panic!("expected deserialize error");
+        };
+        assert_eq!(
+            (e.message.as_str(), e.path_string()),
+            ("missing field `age`", ".".into())
+        );
+        assert_eq!(e.location.unwrap().offset, 0);
+    }
+}
@@ -0,0 +1,353 @@
+//! Structural diff between two [`JsonValue`] trees.
+
+use std::{cmp::Ordering, fmt};
+
+use super::{number::compare_numbers, JsonPointer, JsonValue, Patch, PatchOperation};
+
+/// How arrays are compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayDiff {
+    /// Element `i` is compared with element `i`.
+    #[default]
+    Ordered,
+    /// Order does not matter; only elements without an equal counterpart
+    /// (counting duplicates) are reported.
+    Set,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    pub arrays: ArrayDiff,
+    /// Compare numbers by value, so `Integer(1)` equals `Double(1.0)` and
+    /// `Number("1e0")`.
+    pub numeric_equality: bool,
+}
+
+/// The changes that turn one value into another.
+///
+/// ```
+/// use grammar::json::{self, diff};
+///
+/// let old = json::parse(r#"{"name": "Jack", "marks": [90, 80]}"#)?;
+/// let new = json::parse(r#"{"name": "Jack", "marks": [90, 85], "age": 20}"#)?;
+/// assert_eq!(
+///     diff(&old, &new).to_string(),
+///     "~ \"/marks/1\": 80 -> 85\n+ \"/age\": 20\n"
+/// );
+/// # Ok::<(), grammar::json::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        path: JsonPointer,
+        value: JsonValue,
+    },
+    Removed {
+        path: JsonPointer,
+        value: JsonValue,
+    },
+    Changed {
+        path: JsonPointer,
+        old: JsonValue,
+        new: JsonValue,
+    },
+}
+
+pub fn diff(old: &JsonValue, new: &JsonValue) -> Diff {
+    diff_with(old, new, &DiffOptions::default())
+}
+
+pub fn diff_with(old: &JsonValue, new: &JsonValue, options: &DiffOptions) -> Diff {
+    let mut diff = Diff::default();
+    options.walk(&JsonPointer::root(), old, new, &mut diff.changes);
+    diff
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The changes as a JSON Patch that turns the old value into the new one.
+    /// With [`ArrayDiff::Set`] the patched arrays hold the same elements as
+    /// the new ones, though not necessarily in the same order.
+    pub fn to_patch(&self) -> Patch {
+        Patch(self.changes.iter().map(Change::to_operation).collect())
+    }
+}
+
+impl Change {
+    pub fn path(&self) -> &JsonPointer {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+
+    fn to_operation(&self) -> PatchOperation {
+        match self {
+            Change::Added { path, value } => PatchOperation::Add {
+                path: path.clone(),
+                value: value.clone(),
+            },
+            Change::Removed { path, .. } => PatchOperation::Remove { path: path.clone() },
+            Change::Changed { path, new, .. } => PatchOperation::Replace {
+                path: path.clone(),
+                value: new.clone(),
+            },
+        }
+    }
+}
+
+/// One line per change: `+` added, `-` removed, `~` changed.
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path().to_string();
+        match self {
+            Change::Added { value, .. } => write!(f, "+ {:?}: {}", path, value),
+            Change::Removed { value, .. } => write!(f, "- {:?}: {}", path, value),
+            Change::Changed { old, new, .. } => write!(f, "~ {:?}: {} -> {}", path, old, new),
+        }
+    }
+}
+
+impl DiffOptions {
+    fn walk(&self, path: &JsonPointer, old: &JsonValue, new: &JsonValue, out: &mut Vec<Change>) {
+        match (old, new) {
+            (JsonValue::Object(old), JsonValue::Object(new)) => {
+                for (key, old) in old {
+                    match new.get(key) {
+                        Some(new) => self.walk(&path.join(key.as_str()), old, new, out),
+                        None => out.push(Change::Removed {
+                            path: path.join(key.as_str()),
+                            value: old.clone(),
+                        }),
+                    }
+                }
+                for (key, new) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                    out.push(Change::Added {
+                        path: path.join(key.as_str()),
+                        value: new.clone(),
+                    });
+                }
+            }
+            (JsonValue::Array(old), JsonValue::Array(new)) => match self.arrays {
+                ArrayDiff::Ordered => self.walk_ordered(path, old, new, out),
+                ArrayDiff::Set => self.walk_set(path, old, new, out),
+            },
+            (old, new) if !self.equal(old, new) => out.push(Change::Changed {
+                path: path.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    fn walk_ordered(
+        &self,
+        path: &JsonPointer,
+        old: &[JsonValue],
+        new: &[JsonValue],
+        out: &mut Vec<Change>,
+    ) {
+        for (i, (old, new)) in old.iter().zip(new).enumerate() {
+            self.walk(&path.join(i.to_string()), old, new, out);
+        }
+        // removed from the back, so every index is valid when applied in order
+        for i in (new.len()..old.len()).rev() {
+            out.push(Change::Removed {
+                path: path.join(i.to_string()),
+                value: old[i].clone(),
+            });
+        }
+        for (i, new) in new.iter().enumerate().skip(old.len()) {
+            out.push(Change::Added {
+                path: path.join(i.to_string()),
+                value: new.clone(),
+            });
+        }
+    }
+
+    fn walk_set(
+        &self,
+        path: &JsonPointer,
+        old: &[JsonValue],
+        new: &[JsonValue],
+        out: &mut Vec<Change>,
+    ) {
+        let (old_matched, new_matched) = self.match_elements(old, new);
+        for i in (0..old.len()).rev().filter(|i| !old_matched[*i]) {
+            out.push(Change::Removed {
+                path: path.join(i.to_string()),
+                value: old[i].clone(),
+            });
+        }
+        for i in (0..new.len()).filter(|i| !new_matched[*i]) {
+            out.push(Change::Added {
+                path: path.join(i.to_string()),
+                value: new[i].clone(),
+            });
+        }
+    }
+
+    /// Pair every element with an equal, not yet paired element of the other
+    /// array; returns which elements of each side found a partner.
+    fn match_elements(&self, old: &[JsonValue], new: &[JsonValue]) -> (Vec<bool>, Vec<bool>) {
+        let mut old_matched = vec![false; old.len()];
+        let mut new_matched = vec![false; new.len()];
+        for (i, old) in old.iter().enumerate() {
+            let partner = (0..new.len()).find(|j| !new_matched[*j] && self.equal(old, &new[*j]));
+            if let Some(j) = partner {
+                old_matched[i] = true;
+                new_matched[j] = true;
+            }
+        }
+        (old_matched, new_matched)
+    }
+
+    fn equal(&self, old: &JsonValue, new: &JsonValue) -> bool {
+        match (old, new) {
+            (JsonValue::Object(old), JsonValue::Object(new)) => {
+                old.len() == new.len()
+                    && old
+                        .iter()
+                        .all(|(key, old)| new.get(key).is_some_and(|new| self.equal(old, new)))
+            }
+            (JsonValue::Array(old), JsonValue::Array(new)) => {
+                old.len() == new.len()
+                    && match self.arrays {
+                        ArrayDiff::Ordered => {
+                            old.iter().zip(new).all(|(old, new)| self.equal(old, new))
+                        }
+                        ArrayDiff::Set => {
+                            let (old_matched, _) = self.match_elements(old, new);
+                            old_matched.into_iter().all(|matched| matched)
+                        }
+                    }
+            }
+            (old, new) if self.numeric_equality && old.as_f64().is_some() => {
+                compare_numbers(old, new) == Some(Ordering::Equal)
+            }
+            (old, new) => old == new,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse, parse_with, Dialect, ParseOptions};
+
+    fn changes(old: &str, new: &str, options: &DiffOptions) -> String {
+        diff_with(&parse(old).unwrap(), &parse(new).unwrap(), options).to_string()
+    }
+
+    #[test]
+    fn diff_should_report_object_and_array_changes() {
+        let old = r#"{"name": "Jack", "marks": [90, 80, 70], "address": {"city": "BJ", "zip": 1}}"#;
+        let new = r#"{"name": "Jack", "marks": [90, 85], "address": {"city": "SH"}, "age": 20}"#;
+        assert_eq!(
+            changes(old, new, &DiffOptions::default()),
+            [
+                r#"~ "/marks/1": 80 -> 85"#,
+                r#"- "/marks/2": 70"#,
+                r#"~ "/address/city": "BJ" -> "SH""#,
+                r#"- "/address/zip": 1"#,
+                r#"+ "/age": 20"#,
+                "",
+            ]
+            .join("\n")
+        );
+        assert!(diff(&parse(old).unwrap(), &parse(old).unwrap()).is_empty());
+        assert_eq!(
+            changes("[1]", r#"{"a": [1]}"#, &DiffOptions::default()),
+            "~ \"\": [1] -> {\"a\":[1]}\n"
+        );
+    }
+
+    #[test]
+    fn diff_should_follow_options() {
+        let old = r#"{"tags": ["a", "b", "b"], "score": 1}"#;
+        let new = r#"{"tags": ["b", "c", "a"], "score": 1.0}"#;
+        assert_eq!(
+            changes(old, new, &DiffOptions::default()),
+            [
+                r#"~ "/tags/0": "a" -> "b""#,
+                r#"~ "/tags/1": "b" -> "c""#,
+                r#"~ "/tags/2": "b" -> "a""#,
+                r#"~ "/score": 1 -> 1.0"#,
+                "",
+            ]
+            .join("\n")
+        );
+        let options = DiffOptions {
+            arrays: ArrayDiff::Set,
+            numeric_equality: true,
+        };
+        assert_eq!(
+            changes(old, new, &options),
+            "- \"/tags/2\": \"b\"\n+ \"/tags/1\": \"c\"\n"
+        );
+        assert_eq!(changes(r#"[[1, 2]]"#, r#"[[2.0, 1]]"#, &options), "");
+    }
+
+    #[test]
+    fn diff_should_display_non_finite_numbers() {
+        let options = ParseOptions {
+            dialect: Dialect::JSON5,
+            ..Default::default()
+        };
+        let old = parse_with("{a: NaN, b: Infinity}", &options).unwrap();
+        let new = parse_with("{a: 1, b: -Infinity, c: [NaN]}", &options).unwrap();
+        assert_eq!(
+            diff(&old, &new).to_string(),
+            [
+                r#"~ "/a": NaN -> 1"#,
+                r#"~ "/b": Infinity -> -Infinity"#,
+                r#"+ "/c": [NaN]"#,
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn to_patch_should_turn_old_into_new() {
+        let old = r#"{"a": [1, 2, 3, 4], "b": {"c": true}, "d": "x~/y"}"#;
+        let new = r#"{"a": [1, 5], "b": {"e": null}, "f": [1, 2]}"#;
+        let (mut value, new) = (parse(old).unwrap(), parse(new).unwrap());
+        let patch = diff(&value, &new).to_patch();
+        value.apply_patch(&patch).unwrap();
+        assert_eq!(value, new);
+
+        let options = DiffOptions {
+            arrays: ArrayDiff::Set,
+            ..Default::default()
+        };
+        let (old, new) = (
+            parse(r#"[3, 1, 4, 1, 5]"#).unwrap(),
+            parse(r#"[9, 1, 5, 3, 2]"#).unwrap(),
+        );
+        let mut value = old.clone();
+        value
+            .apply_patch(&diff_with(&old, &new, &options).to_patch())
+            .unwrap();
+        assert!(diff_with(&value, &new, &options).is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+use std::{fmt, io};
+
+use thiserror::Error;
+
+use super::PatchError;
+
+#[derive(Debug, Error)]
+pub enum JsonError {
+    #[error("Failed to parse JSON: {0}")]
+    Parse(Box<ParseError>),
+    #[error("Duplicate key {key:?} at offset {offset}")]
+    DuplicateKey { key: String, offset: usize },
+    #[error("Cannot write non-finite number {0} as JSON")]
+    NonFiniteNumber(f64),
+    #[error("Failed to write JSON: {0}")]
+    Fmt(#[from] fmt::Error),
+    #[error("JSON I/O failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to deserialize JSON: {0}")]
+    Deserialize(Box<DeserializeError>),
+    #[error("Failed to serialize to JSON: {0}")]
+    Serialize(String),
+    #[error("Invalid JSONPath query: {0}")]
+    Path(Box<ParseError>),
+    #[error("{0}")]
+    Patch(Box<PatchError>),
+    #[error("Invalid filter: {0}")]
+    Filter(Box<ParseError>),
+    #[error("Filter failed: {0}")]
+    FilterEval(String),
+    #[error("JSON exceeds the maximum {limit} of {max} at offset {offset}")]
+    LimitExceeded {
+        limit: Limit,
+        max: usize,
+        offset: usize,
+    },
+    #[error("Line {line}: {source}")]
+    Line {
+        line: usize,
+        #[source]
+        source: Box<JsonError>,
+    },
+}
+
+impl From<ParseError> for JsonError {
+    fn from(e: ParseError) -> Self {
+        JsonError::Parse(Box::new(e))
+    }
+}
+
+impl From<PatchError> for JsonError {
+    fn from(e: PatchError) -> Self {
+        JsonError::Patch(Box::new(e))
+    }
+}
+
+impl From<DeserializeError> for JsonError {
+    fn from(e: DeserializeError) -> Self {
+        JsonError::Deserialize(Box::new(e))
+    }
+}
+
+/// Where and why a document was rejected, shared by every parser backend.
+///
+/// `Display` renders the source line with a caret under the failing position:
+///
+/// ```text
+/// invalid array: expected one of `,`, `]`, found `2` at line 1, column 4
+///   |
+/// 1 | [1 2]
+///   |    ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset into the input.
+    pub offset: usize,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// The construct that was being parsed, e.g. `string`.
+    pub label: Option<String>,
+    /// What would have been accepted at `offset`.
+    pub expected: Vec<String>,
+    /// Free-form reason, used when the input is well formed but not acceptable.
+    pub message: Option<String>,
+    /// The character at `offset`, `None` at end of input.
+    pub found: Option<char>,
+    source_line: String,
+}
+
+impl ParseError {
+    pub fn new(input: &str, offset: usize) -> Self {
+        let offset = offset.min(input.len());
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line = input[..offset].matches('\n').count() + 1;
+        let column = input[line_start..offset].chars().count() + 1;
+        let source_line = input[line_start..]
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('\r')
+            .to_string();
+        Self {
+            offset,
+            line,
+            column,
+            label: None,
+            expected: Vec::new(),
+            message: None,
+            found: input[offset..].chars().next(),
+            source_line,
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_expected<T: Into<String>>(mut self, expected: impl IntoIterator<Item = T>) -> Self {
+        for item in expected {
+            let item = item.into();
+            if !self.expected.contains(&item) {
+                self.expected.push(item);
+            }
+        }
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// The source line the error points into.
+    pub fn source_line(&self) -> &str {
+        &self.source_line
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "invalid {}", label)?;
+            if self.message.is_some() || !self.expected.is_empty() {
+                write!(f, ": ")?;
+            }
+        }
+        if let Some(message) = &self.message {
+            write!(f, "{}", message)?;
+        } else if !self.expected.is_empty() {
+            match self.expected.as_slice() {
+                [one] => write!(f, "expected {}", one)?,
+                many => write!(f, "expected one of {}", many.join(", "))?,
+            }
+            match self.found {
+                Some(c) => write!(f, ", found `{}`", c.escape_debug())?,
+                None => write!(f, ", found end of input")?,
+            }
+        } else if self.label.is_none() {
+            write!(f, "unexpected input")?;
+        }
+        writeln!(f, " at line {}, column {}", self.line, self.column)?;
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        let caret = " ".repeat(self.column - 1);
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{} | {}^", gutter, caret)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Which of the [`ParseLimits`](super::ParseLimits) a document exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Depth,
+    StringLength,
+    ArrayLength,
+    ObjectLength,
+    InputLength,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Limit::Depth => "nesting depth",
+            Limit::StringLength => "string length",
+            Limit::ArrayLength => "array length",
+            Limit::ObjectLength => "object length",
+            Limit::InputLength => "input length",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A value that does not fit the type it is deserialized into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError {
+    pub message: String,
+    /// Where the value sits in the document, empty for the root.
+    pub path: Vec<PathSegment>,
+    /// Where the value starts in the source text, when deserializing from one.
+    pub location: Option<ParseError>,
+}
+
+/// One step from a value into its children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl DeserializeError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            path: Vec::new(),
+            location: None,
+        }
+    }
+
+    /// The path rendered as `.marks[2].name`, or `.` for the root.
+    pub fn path_string(&self) -> String {
+        if self.path.is_empty() {
+            return ".".to_string();
+        }
+        self.path.iter().map(PathSegment::to_string).collect()
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Key(key) => write!(f, ".{}", key),
+            PathSegment::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}", location),
+            None => write!(f, "{} at `{}`", self.message, self.path_string()),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_should_locate_offset() {
+        let input = "{\n  \"a\": tru,\n}";
+        let err = ParseError::new(input, 9);
+        assert_eq!((err.line, err.column, err.found), (2, 8, Some('t')));
+        assert_eq!(err.source_line(), "  \"a\": tru,");
+
+        let err = ParseError::new(input, input.len());
+        assert_eq!((err.line, err.column, err.found), (3, 2, None));
+    }
+
+    #[test]
+    fn parse_error_should_render_snippet() {
+        let err = ParseError::new("[1 2]", 3)
+            .with_label("array")
+            .with_expected(["`,`", "`]`", "`]`"]);
+        let expected = "invalid array: expected one of `,`, `]`, found `2` at line 1, column 4
+  |
+1 | [1 2]
+  |    ^";
+        assert_eq!(err.to_string(), expected);
+    }
+}
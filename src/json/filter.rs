@@ -0,0 +1,912 @@
+//! jq-style filters over [`JsonValue`], e.g. `.marks | map(select(. > 85))`.
+//!
+//! The syntax is defined in `filter.pest`, with operator precedence resolved
+//! by pest's [`PrattParser`]. As in jq, a filter turns one input into any
+//! number of outputs: `.[]` yields every element, `,` concatenates the
+//! outputs of both sides and `|` runs the right side once per left output.
+
+use std::{cmp::Ordering, str::FromStr};
+
+use pest::{
+    error::{ErrorVariant, InputLocation},
+    iterators::Pair,
+    pratt_parser::{Assoc, Op, PrattParser},
+    Parser, Span,
+};
+
+use super::{
+    number::compare_numbers, path::values_equal, JsonError, JsonObject, JsonValue, ParseError,
+};
+
+#[derive(Debug, pest_derive::Parser)]
+#[grammar = "json/filter.pest"]
+struct FilterParser;
+
+/// A compiled filter.
+///
+/// ```
+/// use grammar::json::{self, Filter, JsonValue};
+///
+/// let doc = json::parse(r#"{"name": "Jack", "marks": [90, 80, 95]}"#)?;
+/// let filter = Filter::parse(".marks | map(select(. > 85)) | length")?;
+/// assert_eq!(filter.run(&doc)?, [JsonValue::Integer(2)]);
+///
+/// let names = doc.filter(r#"{name, best: (.marks | .[0] + 5)}, .marks[1:]"#)?;
+/// assert_eq!(names[0], json::parse(r#"{"name": "Jack", "best": 95}"#)?);
+/// assert_eq!(names[1], json::parse("[80, 95]")?);
+/// # Ok::<(), grammar::json::JsonError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Identity,
+    Literal(JsonValue),
+    /// `target[key]`, also written `target.key`.
+    Index(Box<Expr>, Box<Expr>),
+    /// `target[from:to]`, a missing bound is `null`.
+    Slice(Box<Expr>, Box<Expr>, Box<Expr>),
+    Iterate(Box<Expr>),
+    /// `[expr]` collects every output of `expr`.
+    Array(Option<Box<Expr>>),
+    Object(Vec<(Expr, Expr)>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Builtin, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Pipe,
+    Comma,
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Builtin {
+    Map,
+    Select,
+    Keys,
+    Length,
+    Not,
+    Empty,
+}
+
+impl Filter {
+    pub fn parse(filter: &str) -> Result<Self, JsonError> {
+        let pair = FilterParser::parse(Rule::filter, filter)
+            .map_err(|e| JsonError::Filter(Box::new(to_parse_error(filter, e))))?
+            .next()
+            .expect("the grammar guarantees an expression");
+        Ok(Self {
+            expr: parse_expr(pair, &pratt())?,
+        })
+    }
+
+    /// Every output of the filter for `input`, in order.
+    pub fn run(&self, input: &JsonValue) -> Result<Vec<JsonValue>, JsonError> {
+        self.expr.eval(input)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = JsonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl JsonValue {
+    /// Compile a jq-style filter and run it against this value.
+    pub fn filter(&self, filter: &str) -> Result<Vec<JsonValue>, JsonError> {
+        Filter::parse(filter)?.run(self)
+    }
+}
+
+/// Operators from the loosest to the tightest binding, as in jq.
+fn pratt() -> PrattParser<Rule> {
+    PrattParser::new()
+        .op(Op::infix(Rule::pipe, Assoc::Right))
+        .op(Op::infix(Rule::comma, Assoc::Left))
+        .op(Op::infix(Rule::or, Assoc::Left))
+        .op(Op::infix(Rule::and, Assoc::Left))
+        .op(Op::infix(Rule::eq, Assoc::Left)
+            | Op::infix(Rule::ne, Assoc::Left)
+            | Op::infix(Rule::lt, Assoc::Left)
+            | Op::infix(Rule::le, Assoc::Left)
+            | Op::infix(Rule::gt, Assoc::Left)
+            | Op::infix(Rule::ge, Assoc::Left))
+        .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+        .op(Op::infix(Rule::mul, Assoc::Left)
+            | Op::infix(Rule::div, Assoc::Left)
+            | Op::infix(Rule::rem, Assoc::Left))
+        .op(Op::prefix(Rule::neg))
+}
+
+impl Expr {
+    fn eval(&self, input: &JsonValue) -> Result<Vec<JsonValue>, JsonError> {
+        let mut out = Vec::new();
+        match self {
+            Expr::Identity => out.push(input.clone()),
+            Expr::Literal(v) => out.push(v.clone()),
+            Expr::Index(target, key) => {
+                let keys = key.eval(input)?;
+                for target in target.eval(input)? {
+                    for key in &keys {
+                        out.push(index(&target, key)?);
+                    }
+                }
+            }
+            Expr::Slice(target, from, to) => {
+                let (from, to) = (from.eval(input)?, to.eval(input)?);
+                for target in target.eval(input)? {
+                    for from in &from {
+                        for to in &to {
+                            out.push(slice(&target, from, to)?);
+                        }
+                    }
+                }
+            }
+            Expr::Iterate(target) => {
+                for target in target.eval(input)? {
+                    out.extend(iterate(&target)?);
+                }
+            }
+            Expr::Array(None) => out.push(JsonValue::Array(Vec::new())),
+            Expr::Array(Some(expr)) => out.push(JsonValue::Array(expr.eval(input)?)),
+            Expr::Object(entries) => {
+                // every combination of the entries' outputs makes an object
+                let mut objects = vec![JsonObject::new()];
+                for (key, value) in entries {
+                    let (keys, values) = (key.eval(input)?, value.eval(input)?);
+                    let mut next = Vec::new();
+                    for obj in &objects {
+                        for key in &keys {
+                            let JsonValue::String(key) = key else {
+                                return Err(eval_error(format!(
+                                    "Object keys must be strings, found {}",
+                                    describe(key)
+                                )));
+                            };
+                            for value in &values {
+                                let mut obj = obj.clone();
+                                obj.insert(key.clone(), value.clone());
+                                next.push(obj);
+                            }
+                        }
+                    }
+                    objects = next;
+                }
+                out.extend(objects.into_iter().map(JsonValue::Object));
+            }
+            Expr::Neg(expr) => {
+                for v in expr.eval(input)? {
+                    if v.as_f64().is_none() {
+                        return Err(eval_error(format!("{} cannot be negated", describe(&v))));
+                    }
+                    out.push(arithmetic(BinaryOp::Sub, &JsonValue::Integer(0), &v)?);
+                }
+            }
+            Expr::Binary(BinaryOp::Pipe, left, right) => {
+                for v in left.eval(input)? {
+                    out.extend(right.eval(&v)?);
+                }
+            }
+            Expr::Binary(BinaryOp::Comma, left, right) => {
+                out.extend(left.eval(input)?);
+                out.extend(right.eval(input)?);
+            }
+            Expr::Binary(op @ (BinaryOp::Or | BinaryOp::And), left, right) => {
+                // the right side only runs when the left does not decide
+                let decisive = *op == BinaryOp::Or;
+                for v in left.eval(input)? {
+                    match truthy(&v) == decisive {
+                        true => out.push(JsonValue::Bool(decisive)),
+                        false => out.extend(
+                            right
+                                .eval(input)?
+                                .iter()
+                                .map(|v| JsonValue::Bool(truthy(v))),
+                        ),
+                    }
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let lefts = left.eval(input)?;
+                for right in right.eval(input)? {
+                    for left in &lefts {
+                        out.push(op.apply(left, &right)?);
+                    }
+                }
+            }
+            Expr::Call(builtin, args) => return builtin.call(args, input),
+        }
+        Ok(out)
+    }
+}
+
+impl BinaryOp {
+    fn apply(self, left: &JsonValue, right: &JsonValue) -> Result<JsonValue, JsonError> {
+        let ordering = || compare(left, right);
+        Ok(JsonValue::Bool(match self {
+            BinaryOp::Eq => values_equal(left, right),
+            BinaryOp::Ne => !values_equal(left, right),
+            BinaryOp::Lt => ordering() == Ordering::Less,
+            BinaryOp::Le => ordering() != Ordering::Greater,
+            BinaryOp::Gt => ordering() == Ordering::Greater,
+            BinaryOp::Ge => ordering() != Ordering::Less,
+            _ => return arithmetic(self, left, right),
+        }))
+    }
+}
+
+impl Builtin {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "map" => Builtin::Map,
+            "select" => Builtin::Select,
+            "keys" => Builtin::Keys,
+            "length" => Builtin::Length,
+            "not" => Builtin::Not,
+            "empty" => Builtin::Empty,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Builtin::Map | Builtin::Select => 1,
+            _ => 0,
+        }
+    }
+
+    fn call(self, args: &[Expr], input: &JsonValue) -> Result<Vec<JsonValue>, JsonError> {
+        Ok(match self {
+            // `[.[] | f]`
+            Builtin::Map => {
+                let mut values = Vec::new();
+                for v in iterate(input)? {
+                    values.extend(args[0].eval(&v)?);
+                }
+                vec![JsonValue::Array(values)]
+            }
+            // the input once for every truthy output of the condition
+            Builtin::Select => args[0]
+                .eval(input)?
+                .iter()
+                .filter(|v| truthy(v))
+                .map(|_| input.clone())
+                .collect(),
+            Builtin::Keys => vec![keys(input)?],
+            Builtin::Length => vec![length(input)?],
+            Builtin::Not => vec![JsonValue::Bool(!truthy(input))],
+            Builtin::Empty => Vec::new(),
+        })
+    }
+}
+
+/// Only `false` and `null` are false.
+fn truthy(v: &JsonValue) -> bool {
+    !matches!(v, JsonValue::Null | JsonValue::Bool(false))
+}
+
+fn eval_error(message: impl Into<String>) -> JsonError {
+    JsonError::FilterEval(message.into())
+}
+
+/// A value for error messages, e.g. `number (1.5)`, shortened when long.
+fn describe(v: &JsonValue) -> String {
+    let text = v.to_string();
+    match text.char_indices().nth(24) {
+        Some((end, _)) => format!("{} ({}...)", v.kind(), &text[..end]),
+        None => format!("{} ({})", v.kind(), text),
+    }
+}
+
+fn index(target: &JsonValue, key: &JsonValue) -> Result<JsonValue, JsonError> {
+    match (target, key) {
+        (JsonValue::Object(obj), JsonValue::String(key)) => {
+            Ok(obj.get(key).cloned().unwrap_or(JsonValue::Null))
+        }
+        (JsonValue::Array(values), key) if key.as_f64().is_some() => {
+            let i = position(key, values.len()).unwrap_or(values.len());
+            Ok(values.get(i).cloned().unwrap_or(JsonValue::Null))
+        }
+        (JsonValue::Null, JsonValue::String(_)) => Ok(JsonValue::Null),
+        (JsonValue::Null, key) if key.as_f64().is_some() => Ok(JsonValue::Null),
+        _ => Err(eval_error(format!(
+            "Cannot index {} with {}",
+            target.kind(),
+            describe(key)
+        ))),
+    }
+}
+
+/// The index `i` refers to in a sequence of `len` items, counting from the
+/// end when negative; `None` before the start.
+fn position(i: &JsonValue, len: usize) -> Option<usize> {
+    let i = i.as_f64()?.floor();
+    let i = if i < 0.0 { i + len as f64 } else { i };
+    (i >= 0.0).then_some(i as usize)
+}
+
+fn slice(target: &JsonValue, from: &JsonValue, to: &JsonValue) -> Result<JsonValue, JsonError> {
+    let bounds = |len: usize| -> Result<(usize, usize), JsonError> {
+        let bound = |v: &JsonValue, default: usize| match v {
+            JsonValue::Null => Ok(default),
+            v if v.as_f64().is_some() => Ok(position(v, len).unwrap_or(0).min(len)),
+            v => Err(eval_error(format!(
+                "Slice bounds must be numbers, found {}",
+                describe(v)
+            ))),
+        };
+        let (from, to) = (bound(from, 0)?, bound(to, len)?);
+        Ok((from, to.max(from)))
+    };
+    match target {
+        JsonValue::Null => Ok(JsonValue::Null),
+        JsonValue::Array(values) => {
+            let (from, to) = bounds(values.len())?;
+            Ok(JsonValue::Array(values[from..to].to_vec()))
+        }
+        JsonValue::String(s) => {
+            let (from, to) = bounds(s.chars().count())?;
+            Ok(JsonValue::String(
+                s.chars().skip(from).take(to - from).collect(),
+            ))
+        }
+        _ => Err(eval_error(format!("Cannot slice {}", describe(target)))),
+    }
+}
+
+fn iterate(target: &JsonValue) -> Result<Vec<JsonValue>, JsonError> {
+    match target {
+        JsonValue::Array(values) => Ok(values.clone()),
+        JsonValue::Object(obj) => Ok(obj.values().cloned().collect()),
+        _ => Err(eval_error(format!(
+            "Cannot iterate over {}",
+            describe(target)
+        ))),
+    }
+}
+
+/// Object keys in sorted order, or the indexes of an array.
+fn keys(v: &JsonValue) -> Result<JsonValue, JsonError> {
+    match v {
+        JsonValue::Object(obj) => {
+            let mut keys: Vec<_> = obj.keys().cloned().collect();
+            keys.sort();
+            Ok(JsonValue::Array(
+                keys.into_iter().map(JsonValue::String).collect(),
+            ))
+        }
+        JsonValue::Array(values) => Ok(JsonValue::Array(
+            (0..values.len() as i64).map(JsonValue::Integer).collect(),
+        )),
+        _ => Err(eval_error(format!("{} has no keys", describe(v)))),
+    }
+}
+
+/// Characters of a string, entries of a container, the absolute value of a
+/// number, and 0 for `null`.
+fn length(v: &JsonValue) -> Result<JsonValue, JsonError> {
+    let len = match v {
+        JsonValue::Null => 0,
+        JsonValue::Bool(_) => return Err(eval_error(format!("{} has no length", describe(v)))),
+        JsonValue::String(s) => s.chars().count(),
+        JsonValue::Array(values) => values.len(),
+        JsonValue::Object(obj) => obj.len(),
+        number => {
+            return match compare(number, &JsonValue::Integer(0)) {
+                Ordering::Less => arithmetic(BinaryOp::Sub, &JsonValue::Integer(0), number),
+                _ => Ok(number.clone()),
+            }
+        }
+    };
+    Ok(JsonValue::Integer(len as i64))
+}
+
+fn arithmetic(op: BinaryOp, left: &JsonValue, right: &JsonValue) -> Result<JsonValue, JsonError> {
+    use JsonValue::*;
+
+    match (op, left, right) {
+        (BinaryOp::Add, Null, v) | (BinaryOp::Add, v, Null) => Ok(v.clone()),
+        (BinaryOp::Add, String(l), String(r)) => Ok(String(format!("{}{}", l, r))),
+        (BinaryOp::Add, Array(l), Array(r)) => Ok(Array(l.iter().chain(r).cloned().collect())),
+        (BinaryOp::Add, Object(l), Object(r)) => {
+            let mut obj = l.clone();
+            obj.extend(r.iter().map(|(k, v)| (k.clone(), v.clone())));
+            Ok(Object(obj))
+        }
+        (BinaryOp::Sub, Array(l), Array(r)) => Ok(Array(
+            l.iter()
+                .filter(|v| !r.iter().any(|r| values_equal(v, r)))
+                .cloned()
+                .collect(),
+        )),
+        _ => match (left.as_f64(), right.as_f64()) {
+            (Some(l), Some(r)) => numeric(op, left, right, l, r),
+            _ => Err(eval_error(format!(
+                "{} and {} cannot be {}",
+                describe(left),
+                describe(right),
+                op.verb()
+            ))),
+        },
+    }
+}
+
+/// Integers stay integers while the result is exact, otherwise the
+/// operation is done in `f64`.
+fn numeric(
+    op: BinaryOp,
+    left: &JsonValue,
+    right: &JsonValue,
+    l: f64,
+    r: f64,
+) -> Result<JsonValue, JsonError> {
+    if matches!(op, BinaryOp::Div | BinaryOp::Rem) && r == 0.0 {
+        return Err(eval_error(format!(
+            "{} and {} cannot be divided because the divisor is zero",
+            describe(left),
+            describe(right)
+        )));
+    }
+    if let (Some(l), Some(r)) = (left.as_i64(), right.as_i64()) {
+        let exact = match op {
+            BinaryOp::Add => l.checked_add(r),
+            BinaryOp::Sub => l.checked_sub(r),
+            BinaryOp::Mul => l.checked_mul(r),
+            BinaryOp::Div => l
+                .checked_rem(r)
+                .filter(|rem| *rem == 0)
+                .and(l.checked_div(r)),
+            _ => l.checked_rem(r),
+        };
+        if let Some(v) = exact {
+            return Ok(JsonValue::Integer(v));
+        }
+    }
+    Ok(JsonValue::Double(match op {
+        BinaryOp::Add => l + r,
+        BinaryOp::Sub => l - r,
+        BinaryOp::Mul => l * r,
+        BinaryOp::Div => l / r,
+        // like jq, the remainder of the operands truncated to integers
+        _ => (l.trunc() % r.trunc()).trunc(),
+    }))
+}
+
+impl BinaryOp {
+    fn verb(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "added",
+            BinaryOp::Sub => "subtracted",
+            BinaryOp::Mul => "multiplied",
+            _ => "divided",
+        }
+    }
+}
+
+/// jq's total order: `null < false < true < numbers < strings < arrays <
+/// objects`. Arrays compare element-wise; objects by their sorted keys first
+/// and then by the values under those keys.
+fn compare(left: &JsonValue, right: &JsonValue) -> Ordering {
+    fn rank(v: &JsonValue) -> u8 {
+        match v {
+            JsonValue::Null => 0,
+            JsonValue::Bool(false) => 1,
+            JsonValue::Bool(true) => 2,
+            JsonValue::Integer(_) | JsonValue::Double(_) | JsonValue::Number(_) => 3,
+            JsonValue::String(_) => 4,
+            JsonValue::Array(_) => 5,
+            JsonValue::Object(_) => 6,
+        }
+    }
+
+    match (left, right) {
+        (JsonValue::String(l), JsonValue::String(r)) => l.cmp(r),
+        (JsonValue::Array(l), JsonValue::Array(r)) => l
+            .iter()
+            .zip(r)
+            .map(|(l, r)| compare(l, r))
+            .find(|o| o.is_ne())
+            .unwrap_or(l.len().cmp(&r.len())),
+        (JsonValue::Object(l), JsonValue::Object(r)) => {
+            let mut keys: Vec<_> = l.keys().collect();
+            let mut other: Vec<_> = r.keys().collect();
+            keys.sort();
+            other.sort();
+            keys.cmp(&other).then_with(|| {
+                keys.iter()
+                    .map(|k| compare(&l[*k], &r[*k]))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left)
+            .cmp(&rank(right))
+            .then_with(|| compare_numbers(left, right).unwrap_or(Ordering::Equal)),
+    }
+}
+
+fn to_parse_error(input: &str, e: pest::error::Error<Rule>) -> ParseError {
+    let offset = match e.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    };
+    let err = ParseError::new(input, offset);
+    match e.variant {
+        ErrorVariant::ParsingError { positives, .. } => {
+            err.with_expected(positives.into_iter().map(describe_rule))
+        }
+        ErrorVariant::CustomError { message } => err.with_message(message),
+    }
+}
+
+fn describe_rule(rule: Rule) -> &'static str {
+    match rule {
+        Rule::expr | Rule::object_value | Rule::term | Rule::slice_from | Rule::slice_to => {
+            "filter"
+        }
+        Rule::pipe => "`|`",
+        Rule::comma => "`,`",
+        Rule::or | Rule::and => "boolean operator",
+        Rule::eq | Rule::ne | Rule::le | Rule::ge | Rule::lt | Rule::gt => "comparison operator",
+        Rule::add | Rule::sub | Rule::mul | Rule::div | Rule::rem => "arithmetic operator",
+        Rule::neg => "`-`",
+        Rule::identity | Rule::field => "path",
+        Rule::iterate | Rule::slice | Rule::index => "`[`",
+        Rule::array => "array",
+        Rule::object => "object",
+        Rule::entry => "object entry",
+        Rule::call | Rule::ident => "function",
+        Rule::number => "number",
+        Rule::string | Rule::chars => "string",
+        Rule::true_lit | Rule::false_lit | Rule::null_lit => "literal",
+        Rule::EOI => "end of filter",
+        _ => "token",
+    }
+}
+
+/// An error for a filter the grammar accepted but that is not well formed.
+fn invalid(span: Span, message: impl Into<String>) -> JsonError {
+    let err = ParseError::new(span.get_input(), span.start()).with_message(message);
+    JsonError::Filter(Box::new(err))
+}
+
+fn first_inner(pair: Pair<Rule>) -> Pair<Rule> {
+    pair.into_inner()
+        .next()
+        .expect("the grammar guarantees an inner pair")
+}
+
+/// Parse an `expr` or `object_value`: operands separated by operators.
+fn parse_expr(pair: Pair<Rule>, pratt: &PrattParser<Rule>) -> Result<Expr, JsonError> {
+    pratt
+        .map_primary(|term| parse_term(term, pratt))
+        .map_prefix(|_, expr| Ok(Expr::Neg(Box::new(expr?))))
+        .map_infix(|left, op, right| {
+            let op = match op.as_rule() {
+                Rule::pipe => BinaryOp::Pipe,
+                Rule::comma => BinaryOp::Comma,
+                Rule::or => BinaryOp::Or,
+                Rule::and => BinaryOp::And,
+                Rule::eq => BinaryOp::Eq,
+                Rule::ne => BinaryOp::Ne,
+                Rule::lt => BinaryOp::Lt,
+                Rule::le => BinaryOp::Le,
+                Rule::gt => BinaryOp::Gt,
+                Rule::ge => BinaryOp::Ge,
+                Rule::add => BinaryOp::Add,
+                Rule::sub => BinaryOp::Sub,
+                Rule::mul => BinaryOp::Mul,
+                Rule::div => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            Ok(Expr::Binary(op, Box::new(left?), Box::new(right?)))
+        })
+        .parse(pair.into_inner())
+}
+
+/// A primary and its `.name`, `[]`, `[i]` and `[from:to]` suffixes.
+fn parse_term(pair: Pair<Rule>, pratt: &PrattParser<Rule>) -> Result<Expr, JsonError> {
+    let mut inner = pair.into_inner();
+    let primary = inner.next().expect("the grammar guarantees a primary");
+    let mut expr = parse_primary(primary, pratt)?;
+    for suffix in inner {
+        let target = Box::new(expr);
+        expr = match suffix.as_rule() {
+            Rule::field => Expr::Index(target, Box::new(parse_name(first_inner(suffix))?)),
+            Rule::iterate => Expr::Iterate(target),
+            Rule::index => Expr::Index(target, Box::new(parse_expr(first_inner(suffix), pratt)?)),
+            Rule::slice => {
+                let (mut from, mut to) = (
+                    Expr::Literal(JsonValue::Null),
+                    Expr::Literal(JsonValue::Null),
+                );
+                for bound in suffix.into_inner() {
+                    let is_from = bound.as_rule() == Rule::slice_from;
+                    let bound = parse_expr(first_inner(bound), pratt)?;
+                    match is_from {
+                        true => from = bound,
+                        false => to = bound,
+                    }
+                }
+                Expr::Slice(target, Box::new(from), Box::new(to))
+            }
+            _ => unreachable!(),
+        };
+    }
+    Ok(expr)
+}
+
+fn parse_primary(pair: Pair<Rule>, pratt: &PrattParser<Rule>) -> Result<Expr, JsonError> {
+    Ok(match pair.as_rule() {
+        Rule::identity => Expr::Identity,
+        Rule::field => Expr::Index(
+            Box::new(Expr::Identity),
+            Box::new(parse_name(first_inner(pair))?),
+        ),
+        Rule::number => Expr::Literal(parse_number(&pair)?),
+        Rule::string => Expr::Literal(JsonValue::String(parse_string(pair)?)),
+        Rule::true_lit => Expr::Literal(JsonValue::Bool(true)),
+        Rule::false_lit => Expr::Literal(JsonValue::Bool(false)),
+        Rule::null_lit => Expr::Literal(JsonValue::Null),
+        Rule::array => match pair.into_inner().next() {
+            Some(expr) => Expr::Array(Some(Box::new(parse_expr(expr, pratt)?))),
+            None => Expr::Array(None),
+        },
+        Rule::object => Expr::Object(
+            pair.into_inner()
+                .map(|entry| parse_entry(entry, pratt))
+                .collect::<Result<_, _>>()?,
+        ),
+        Rule::call => parse_call(pair, pratt)?,
+        Rule::expr => parse_expr(pair, pratt)?,
+        _ => unreachable!(),
+    })
+}
+
+fn parse_entry(pair: Pair<Rule>, pratt: &PrattParser<Rule>) -> Result<(Expr, Expr), JsonError> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let key = inner.next().expect("the grammar guarantees a key");
+    let is_name = key.as_rule() != Rule::expr;
+    let key = match is_name {
+        true => parse_name(key)?,
+        false => parse_expr(key, pratt)?,
+    };
+    let value = match inner.next() {
+        Some(value) => parse_expr(value, pratt)?,
+        // `{name}` is short for `{name: .name}`
+        None if is_name => Expr::Index(Box::new(Expr::Identity), Box::new(key.clone())),
+        None => return Err(invalid(span, "a computed key needs a value")),
+    };
+    Ok((key, value))
+}
+
+fn parse_call(pair: Pair<Rule>, pratt: &PrattParser<Rule>) -> Result<Expr, JsonError> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let name = inner
+        .next()
+        .expect("the grammar guarantees a function name");
+    let builtin = Builtin::parse(name.as_str()).ok_or_else(|| {
+        invalid(
+            name.as_span(),
+            format!("unknown function `{}`", name.as_str()),
+        )
+    })?;
+    let args = inner
+        .map(|arg| parse_expr(arg, pratt))
+        .collect::<Result<Vec<_>, _>>()?;
+    if args.len() != builtin.arity() {
+        return Err(invalid(
+            span,
+            format!(
+                "function `{}` takes {} argument(s), found {}",
+                name.as_str(),
+                builtin.arity(),
+                args.len()
+            ),
+        ));
+    }
+    Ok(Expr::Call(builtin, args))
+}
+
+/// The key of `.name`, `."name"` or an object entry, as a string literal.
+fn parse_name(pair: Pair<Rule>) -> Result<Expr, JsonError> {
+    let name = match pair.as_rule() {
+        Rule::ident => pair.as_str().to_string(),
+        _ => parse_string(pair)?,
+    };
+    Ok(Expr::Literal(JsonValue::String(name)))
+}
+
+fn parse_string(pair: Pair<Rule>) -> Result<String, JsonError> {
+    let chars = first_inner(pair);
+    super::pest::unescape(chars.as_str()).map_err(|message| invalid(chars.as_span(), message))
+}
+
+fn parse_number(pair: &Pair<Rule>) -> Result<JsonValue, JsonError> {
+    let s = pair.as_str();
+    if !s.contains(['.', 'e', 'E']) {
+        if let Ok(v) = s.parse() {
+            return Ok(JsonValue::Integer(v));
+        }
+    }
+    s.parse()
+        .map(JsonValue::Double)
+        .map_err(|e| invalid(pair.as_span(), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse;
+
+    const DOC: &str = r#"{"name": "Jack", "marks": [90, 80.5, 95, 70],
+        "address": {"city": "Paris", "zip": "75001"}, "tags": [], "nick": null}"#;
+
+    fn run(filter: &str) -> Vec<JsonValue> {
+        let doc = parse(DOC).unwrap();
+        doc.filter(filter)
+            .unwrap_or_else(|e| panic!("{}: {}", filter, e))
+    }
+
+    fn json(s: &str) -> JsonValue {
+        parse(s).unwrap()
+    }
+
+    #[test]
+    fn filter_should_follow_paths() {
+        assert_eq!(run("."), [json(DOC)]);
+        assert_eq!(run(".address.city"), [json(r#""Paris""#)]);
+        assert_eq!(run(r#"."address"["zip"]"#), [json(r#""75001""#)]);
+        assert_eq!(run(".marks[1]"), [json("80.5")]);
+        assert_eq!(run(".marks[-1]"), [json("70")]);
+        assert_eq!(
+            run(".marks[9], .missing.x, .nick[0]"),
+            [JsonValue::Null, JsonValue::Null, JsonValue::Null]
+        );
+        assert_eq!(run(".marks[1:3]"), [json("[80.5, 95]")]);
+        assert_eq!(run(".marks[-2:]"), [json("[95, 70]")]);
+        assert_eq!(run(".name[:2]"), [json(r#""Ja""#)]);
+        assert_eq!(run(".address[]"), [json(r#""Paris""#), json(r#""75001""#)]);
+        assert_eq!(run(".tags[]"), []);
+    }
+
+    #[test]
+    fn filter_should_apply_operators_by_precedence() {
+        assert_eq!(run("1 + 2 * 3 - -4"), [json("11")]);
+        assert_eq!(
+            run("(1 + 2) * 3, 7 / 2, 8 / 2, 7 % 3"),
+            [json("9"), json("3.5"), json("4"), json("1")]
+        );
+        assert_eq!(run(".marks[0] > 85 and .name == \"Jack\""), [json("true")]);
+        assert_eq!(
+            run("null or false, 1 < 2 == true"),
+            [json("false"), json("true")]
+        );
+        assert_eq!(
+            run(".marks[0] == 90.0, [1, 2] < [1, 3], {} > []"),
+            [json("true"), json("true"), json("true")]
+        );
+        assert_eq!(
+            run(".name + \"!\", .tags + [1] + null"),
+            [json(r#""Jack!""#), json("[1]")]
+        );
+        assert_eq!(run("[1, 2, 3, 2] - [2]"), [json("[1, 3]")]);
+        // binary operators combine every output of both sides
+        assert_eq!(
+            run("(1, 2) + (10, 20)"),
+            [json("11"), json("12"), json("21"), json("22")]
+        );
+        assert_eq!(
+            run(".marks[] | . - 70 | select(. > 15)"),
+            [json("20"), json("25")]
+        );
+    }
+
+    #[test]
+    fn filter_should_call_builtins_and_construct_values() {
+        assert_eq!(run(".marks | map(select(. > 85)) | length"), [json("2")]);
+        assert_eq!(run(".address | keys"), [json(r#"["city", "zip"]"#)]);
+        assert_eq!(run(".marks | keys | length"), [json("4")]);
+        assert_eq!(
+            run(".name, .nick, -3 | length"),
+            [json("4"), json("0"), json("3")]
+        );
+        assert_eq!(
+            run(".nick | not, empty, (.tags | length)"),
+            [json("true"), json("0")]
+        );
+        assert_eq!(run("[.marks[] | select(. < 85)]"), [json("[80.5, 70]")]);
+        assert_eq!(
+            run(r#"{name, "city": .address.city, (.name): 1, best: .marks[0] + 1}"#),
+            [json(
+                r#"{"name": "Jack", "city": "Paris", "Jack": 1, "best": 91}"#
+            )]
+        );
+        assert_eq!(
+            run("{a: (1, 2), b: (3, 4)} | [.a, .b]"),
+            [
+                json("[1, 3]"),
+                json("[1, 4]"),
+                json("[2, 3]"),
+                json("[2, 4]")
+            ]
+        );
+        assert_eq!(run("[], {}"), [json("[]"), json("{}")]);
+    }
+
+    #[test]
+    fn filter_should_report_errors() {
+        for filter in [
+            "",
+            ".a |",
+            ". foo(",
+            "[1",
+            "{(.a)}",
+            "map",
+            "select(1; 2)",
+            "foo",
+            ".a.",
+            "1 +",
+        ] {
+            assert!(
+                matches!(Filter::parse(filter), Err(JsonError::Filter(_))),
+                "{:?}",
+                filter
+            );
+        }
+        let err = Filter::parse(".marks | lenght").unwrap_err().to_string();
+        assert!(
+            err.starts_with("Invalid filter: unknown function `lenght`"),
+            "{}",
+            err
+        );
+        assert!(err.contains("column 10"), "{}", err);
+
+        let doc = json(DOC);
+        for (filter, message) in [
+            (
+                ".name.first",
+                r#"Cannot index string with string ("first")"#,
+            ),
+            (".marks[]  | .[]", "Cannot iterate over number (90)"),
+            (
+                ".name + 1",
+                r#"string ("Jack") and number (1) cannot be added"#,
+            ),
+            ("1 / 0", "cannot be divided because the divisor is zero"),
+            (
+                "{(.marks): 1}",
+                "Object keys must be strings, found array ([90,80.5,95,70])",
+            ),
+            ("true | length", "boolean (true) has no length"),
+            ("-.name", r#"string ("Jack") cannot be negated"#),
+        ] {
+            let err = doc.filter(filter).unwrap_err();
+            assert!(matches!(err, JsonError::FilterEval(_)), "{}", filter);
+            assert!(err.to_string().contains(message), "{}: {}", filter, err);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+mod backend;
+pub mod borrowed;
+mod de;
+mod diff;
+mod error;
+mod filter;
+mod ndjson;
+mod number;
+mod patch;
+mod path;
+pub mod pest;
+mod pointer;
+mod schema;
+mod ser;
+mod stream;
+pub mod winnow;
+mod writer;
+
+use indexmap::IndexMap;
+
+pub use backend::{backend, JsonBackend, PestBackend, WinnowBackend, BACKENDS};
+pub use de::{from_str, from_str_with, from_value};
+pub use diff::{diff, diff_with, ArrayDiff, Change, Diff, DiffOptions};
+pub use error::{DeserializeError, JsonError, Limit, ParseError, PathSegment};
+pub use filter::Filter;
+pub use ndjson::{NdjsonOptions, NdjsonReader, NdjsonRecords, NdjsonWriter};
+pub use number::{InvalidNumber, Number};
+pub use patch::{Patch, PatchError, PatchOperation};
+pub use path::{JsonPath, NormalizedPath, PathNode};
+pub use pointer::{JsonPointer, PointerError};
+pub use schema::{Schema, SchemaError, ValidationError};
+pub use ser::to_value;
+pub use stream::{StreamItem, StreamParser};
+pub use writer::{to_string, write, write_io, WriteOptions};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Double(f64),
+    /// A number kept as written, see [`ParseOptions::lossless_numbers`].
+    Number(Number),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(JsonObject),
+}
+
+/// Object members in the order they appear in the document.
+pub type JsonObject = IndexMap<String, JsonValue>;
+
+/// What to do when an object contains the same key more than once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the value of the last occurrence, at the position of the first.
+    #[default]
+    LastWins,
+    /// Keep the value of the first occurrence and ignore the others.
+    FirstWins,
+    /// Fail with [`JsonError::DuplicateKey`].
+    Error,
+}
+
+/// Extensions to RFC 8259, as found in JSONC and JSON5 files. Strict JSON,
+/// with every extension off, is the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dialect {
+    /// `// line` and `/* block */` comments wherever whitespace may appear.
+    pub comments: bool,
+    /// A comma after the last array element or object member.
+    pub trailing_commas: bool,
+    /// Strings in single quotes, in which `\'` escapes the quote.
+    pub single_quotes: bool,
+    /// Object keys written as identifiers, `[A-Za-z_$][A-Za-z0-9_$]*`.
+    pub unquoted_keys: bool,
+    /// Hexadecimal integers such as `0x1F` or `-0xff`.
+    pub hex_numbers: bool,
+    /// `Infinity` and `NaN`, either with a `-`.
+    pub non_finite_numbers: bool,
+    /// A leading `+` on numbers, e.g. `+1`, `+0x1F` or, with
+    /// `non_finite_numbers`, `+Infinity`.
+    pub plus_signs: bool,
+}
+
+impl Dialect {
+    pub const STRICT: Dialect = Dialect {
+        comments: false,
+        trailing_commas: false,
+        single_quotes: false,
+        unquoted_keys: false,
+        hex_numbers: false,
+        non_finite_numbers: false,
+        plus_signs: false,
+    };
+
+    /// Comments and trailing commas, as in VS Code settings files.
+    pub const JSONC: Dialect = Dialect {
+        comments: true,
+        trailing_commas: true,
+        ..Dialect::STRICT
+    };
+
+    /// Every extension.
+    pub const JSON5: Dialect = Dialect {
+        comments: true,
+        trailing_commas: true,
+        single_quotes: true,
+        unquoted_keys: true,
+        hex_numbers: true,
+        non_finite_numbers: true,
+        plus_signs: true,
+    };
+
+    pub fn is_strict(&self) -> bool {
+        *self == Dialect::STRICT
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub duplicate_keys: DuplicateKeys,
+    pub dialect: Dialect,
+    /// Keep every number as a [`Number`] instead of converting it to an
+    /// `Integer` or `Double`, so it is written back exactly as parsed.
+    pub lossless_numbers: bool,
+    pub limits: ParseLimits,
+}
+
+/// Bounds on untrusted input, checked while parsing. Exceeding one fails with
+/// [`JsonError::LimitExceeded`] at the offending position. Lengths are in
+/// bytes for strings and the input, and in entries for arrays and objects.
+///
+/// Only the nesting depth is bounded by default: deep enough for real data,
+/// and shallow enough that the recursive parsers fit in a 2 MiB thread stack
+/// even in debug builds, where each level costs tens of kilobytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// How many arrays and objects may enclose each other.
+    pub max_depth: usize,
+    /// Longest string or key, after unescaping.
+    pub max_string_len: usize,
+    pub max_array_len: usize,
+    pub max_object_len: usize,
+    pub max_input_len: usize,
+}
+
+impl ParseLimits {
+    /// No limits at all, only for trusted input.
+    pub const UNLIMITED: ParseLimits = ParseLimits {
+        max_depth: usize::MAX,
+        max_string_len: usize::MAX,
+        max_array_len: usize::MAX,
+        max_object_len: usize::MAX,
+        max_input_len: usize::MAX,
+    };
+
+    pub(crate) fn check_input_len(&self, input: &str) -> Result<(), JsonError> {
+        match input.len() > self.max_input_len {
+            true => Err(JsonError::LimitExceeded {
+                limit: Limit::InputLength,
+                max: self.max_input_len,
+                offset: self.max_input_len,
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            ..Self::UNLIMITED
+        }
+    }
+}
+
+/// Parse a JSON document with the default (winnow) backend.
+pub fn parse(input: &str) -> Result<JsonValue, JsonError> {
+    winnow::parse(input)
+}
+
+/// Parse a JSON document with the default (winnow) backend and custom options.
+pub fn parse_with(input: &str, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+    winnow::parse_with(input, options)
+}
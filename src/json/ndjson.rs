@@ -0,0 +1,296 @@
+//! Newline-delimited JSON (NDJSON / JSON Lines): one compact value per line.
+
+use std::{
+    io::{self, BufRead},
+    marker::PhantomData,
+    str,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    from_str_with, to_value, winnow, write_io, JsonError, JsonValue, ParseError, ParseOptions,
+    WriteOptions,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NdjsonOptions {
+    pub parse: ParseOptions,
+    /// Skip lines holding only whitespace instead of failing on them.
+    pub skip_blank: bool,
+    /// Skip lines that fail to parse or deserialize instead of yielding the
+    /// error; they are counted in [`NdjsonReader::skipped`].
+    pub skip_invalid: bool,
+}
+
+/// Reads one [`JsonValue`] per line from any [`BufRead`]. Errors carry the
+/// 1-based line number as [`JsonError::Line`]; reading continues with the
+/// next line after a parse error or invalid UTF-8, but not after an I/O error.
+///
+/// ```
+/// use grammar::json::{JsonValue, NdjsonReader, NdjsonWriter};
+///
+/// let mut writer = NdjsonWriter::new(Vec::new());
+/// writer.write(&JsonValue::Integer(1))?;
+/// writer.serialize(&vec!["a", "b"])?;
+/// let output = writer.into_inner();
+/// assert_eq!(output, b"1\n[\"a\",\"b\"]\n");
+///
+/// let values: Vec<Vec<String>> = NdjsonReader::new(&b"[\"x\"]\n[]\n"[..])
+///     .records()
+///     .collect::<Result<_, _>>()?;
+/// assert_eq!(values, [vec!["x".to_string()], vec![]]);
+/// # Ok::<(), grammar::json::JsonError>(())
+/// ```
+#[derive(Debug)]
+pub struct NdjsonReader<R> {
+    reader: R,
+    options: NdjsonOptions,
+    buf: Vec<u8>,
+    line: usize,
+    skipped: usize,
+    done: bool,
+}
+
+/// Deserializes one `T` per line, see [`NdjsonReader::records`].
+#[derive(Debug)]
+pub struct NdjsonRecords<R, T> {
+    reader: NdjsonReader<R>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, NdjsonOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: NdjsonOptions) -> Self {
+        Self {
+            reader,
+            options,
+            buf: Vec::new(),
+            line: 0,
+            skipped: 0,
+            done: false,
+        }
+    }
+
+    /// Number of the line read last, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// How many blank or invalid lines were skipped so far.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Yield each line deserialized into a `T` instead of a [`JsonValue`].
+    pub fn records<T: DeserializeOwned>(self) -> NdjsonRecords<R, T> {
+        NdjsonRecords {
+            reader: self,
+            _marker: PhantomData,
+        }
+    }
+
+    fn next_with<T>(
+        &mut self,
+        parse: impl Fn(&str, &ParseOptions) -> Result<T, JsonError>,
+    ) -> Option<Result<T, JsonError>> {
+        loop {
+            if self.done {
+                return None;
+            }
+            self.buf.clear();
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok(_) => self.line += 1,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(self.at_line(e.into())));
+                }
+            }
+
+            let result = match str::from_utf8(&self.buf) {
+                Ok(text) => {
+                    let text = text.trim_end_matches(['\n', '\r']);
+                    match text.trim().is_empty() {
+                        true if self.options.skip_blank => {
+                            self.skipped += 1;
+                            continue;
+                        }
+                        true => Err(ParseError::new(text, 0).with_message("blank line").into()),
+                        false => parse(text, &self.options.parse),
+                    }
+                }
+                Err(e) => {
+                    let text = str::from_utf8(&self.buf[..e.valid_up_to()]).unwrap_or_default();
+                    Err(ParseError::new(text, text.len())
+                        .with_message("invalid UTF-8")
+                        .into())
+                }
+            };
+            match result {
+                Err(_) if self.options.skip_invalid => self.skipped += 1,
+                result => return Some(result.map_err(|e| self.at_line(e))),
+            }
+        }
+    }
+
+    fn at_line(&self, e: JsonError) -> JsonError {
+        JsonError::Line {
+            line: self.line,
+            source: Box::new(e),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<JsonValue, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(winnow::parse_with)
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for NdjsonRecords<R, T> {
+    type Item = Result<T, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.next_with(from_str_with)
+    }
+}
+
+/// Writes one compact value per line into any [`io::Write`].
+#[derive(Debug)]
+pub struct NdjsonWriter<W> {
+    writer: W,
+}
+
+impl<W: io::Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn write(&mut self, value: &JsonValue) -> Result<(), JsonError> {
+        // compact output escapes every newline inside strings
+        write_io(&mut self.writer, value, &WriteOptions::compact())?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn serialize<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.write(&to_value(value)?)
+    }
+
+    pub fn flush(&mut self) -> Result<(), JsonError> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Event {
+        id: u64,
+        kind: String,
+    }
+
+    fn lines(e: &JsonError) -> usize {
+        match e {
+            JsonError::Line { line, .. } => *line,
+            e => panic!("expected line error, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn reader_should_report_line_numbers() {
+        let input = "{\"id\": 1, \"kind\": \"a\"}\r\n\n{\"id\": 2\n{\"id\": \"3\", \"kind\": \"c\"}\n{\"id\": 4, \"kind\": \"d\"}";
+        let results: Vec<_> = NdjsonReader::new(input.as_bytes()).collect();
+        assert_eq!(results.len(), 5);
+        assert!(results[0].is_ok() && results[3].is_ok() && results[4].is_ok());
+        assert_eq!(lines(results[1].as_ref().unwrap_err()), 2);
+        let e = results[2].as_ref().unwrap_err();
+        assert_eq!(lines(e), 3);
+        assert!(
+            e.to_string().starts_with("Line 3: Failed to parse JSON"),
+            "{}",
+            e
+        );
+
+        let records: Vec<Result<Event, _>> =
+            NdjsonReader::new(input.as_bytes()).records().collect();
+        assert_eq!(
+            records[0].as_ref().unwrap(),
+            &Event {
+                id: 1,
+                kind: "a".into()
+            }
+        );
+        assert_eq!(lines(records[3].as_ref().unwrap_err()), 4);
+    }
+
+    #[test]
+    fn reader_should_skip_when_asked() {
+        let input = "{\"id\": 1, \"kind\": \"a\"}\n   \n[1,\n{\"id\": -2, \"kind\": \"b\"}\n{\"id\": 3, \"kind\": \"c\"}\n";
+        let options = NdjsonOptions {
+            skip_blank: true,
+            skip_invalid: true,
+            ..Default::default()
+        };
+        let mut reader = NdjsonReader::with_options(input.as_bytes(), options).records();
+        let ids: Vec<u64> = reader
+            .by_ref()
+            .map(|r: Result<Event, _>| r.unwrap().id)
+            .collect();
+        assert_eq!(ids, [1, 3]);
+        assert_eq!((reader.reader.line(), reader.reader.skipped()), (5, 3));
+    }
+
+    #[test]
+    fn reader_should_report_and_skip_invalid_utf8() {
+        let input = b"1\n\"a\xff\"\n2\n";
+        let results: Vec<_> = NdjsonReader::new(&input[..]).collect();
+        assert_eq!(results.len(), 3);
+        let e = results[1].as_ref().unwrap_err();
+        assert_eq!(lines(e), 2);
+        assert!(e.to_string().contains("invalid UTF-8"), "{}", e);
+        assert_eq!(results[2].as_ref().unwrap(), &JsonValue::Integer(2));
+
+        let options = NdjsonOptions {
+            skip_invalid: true,
+            ..Default::default()
+        };
+        let mut reader = NdjsonReader::with_options(&input[..], options);
+        let values: Vec<_> = reader.by_ref().map(Result::unwrap).collect();
+        assert_eq!(values, [JsonValue::Integer(1), JsonValue::Integer(2)]);
+        assert_eq!(reader.skipped(), 1);
+    }
+
+    #[test]
+    fn writer_output_should_read_back() {
+        let values = [
+            JsonValue::String("multi\nline".into()),
+            crate::json::parse(r#"{"a": [1, 2.5, null], "b": {"c": "d"}}"#).unwrap(),
+        ];
+        let mut writer = NdjsonWriter::new(Vec::new());
+        for value in &values {
+            writer.write(value).unwrap();
+        }
+        let output = writer.into_inner();
+        assert_eq!(output.iter().filter(|b| **b == b'\n').count(), 2);
+        let back: Vec<_> = NdjsonReader::new(&output[..]).map(Result::unwrap).collect();
+        assert_eq!(back, values);
+    }
+}
@@ -0,0 +1,310 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use thiserror::Error;
+
+use super::JsonValue;
+
+/// A JSON number kept exactly as written, e.g. `9007199254740993` or `0.10`.
+///
+/// Parsing with [`ParseOptions::lossless_numbers`](super::ParseOptions)
+/// produces [`JsonValue::Number`] instead of `Integer`/`Double`, and the
+/// writer emits the original lexeme, so IDs and amounts round-trip exactly.
+///
+/// ```
+/// use grammar::json::{self, JsonValue, Number, ParseOptions};
+///
+/// let options = ParseOptions { lossless_numbers: true, ..Default::default() };
+/// let v = json::parse_with(r#"[18446744073709551615, 0.10, 1.5e3]"#, &options)?;
+/// assert_eq!(v.to_string(), "[18446744073709551615,0.10,1.5e3]");
+///
+/// let n: Number = "1.5e3".parse()?;
+/// assert_eq!((n.as_i64(), n.as_f64()), (Some(1500), 1500.0));
+/// assert_eq!(n.to_decimal_string().as_deref(), Some("1500"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Number(String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid JSON number {0:?}")]
+pub struct InvalidNumber(String);
+
+/// Exponents beyond this are not expanded when checking for integer values,
+/// so `1e999999999` cannot allocate a billion digits by accident.
+const MAX_INTEGER_EXPONENT: i64 = 40;
+
+/// The most zeros [`Number::to_decimal_string`] pads with before giving up.
+const MAX_DECIMAL_PADDING: u64 = 4096;
+
+impl Number {
+    /// The lexeme, which the winnow parser has already checked.
+    pub(crate) fn from_lexeme(lexeme: &str) -> Self {
+        Self(lexeme.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the lexeme has neither a fraction nor an exponent.
+    pub fn is_integer(&self) -> bool {
+        !self.0.contains(['.', 'e', 'E'])
+    }
+
+    /// The value as an `i64`, if it is an integer within range. `1.0` and
+    /// `1e2` count as integers.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.integer_string()?.parse().ok()
+    }
+
+    /// The value as a `u64`, if it is a non-negative integer within range.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.integer_string()?.parse().ok()
+    }
+
+    /// The nearest `f64`; too large magnitudes become infinite.
+    pub fn as_f64(&self) -> f64 {
+        self.0.parse().unwrap_or(f64::NAN)
+    }
+
+    /// The exact value in plain decimal notation, without exponent, leading
+    /// zeros or trailing fraction zeros, e.g. `-1.250e2` is `-125`. `None`
+    /// when that would take more than a few thousand zeros, as for `1e-999999`.
+    pub fn to_decimal_string(&self) -> Option<String> {
+        let (negative, digits, point) = self.decompose();
+        let padding = match point {
+            p if p <= 0 => p.unsigned_abs(),
+            p => (p as u64).saturating_sub(digits.len() as u64),
+        };
+        if padding > MAX_DECIMAL_PADDING {
+            return None;
+        }
+        let mut s = match point {
+            p if p <= 0 => format!("0.{}{}", "0".repeat(p.unsigned_abs() as usize), digits),
+            p if p as usize >= digits.len() => {
+                format!("{}{}", digits, "0".repeat(p as usize - digits.len()))
+            }
+            p => format!("{}.{}", &digits[..p as usize], &digits[p as usize..]),
+        };
+        if s.contains('.') {
+            s.truncate(s.trim_end_matches('0').trim_end_matches('.').len());
+        }
+        let s = match s.trim_start_matches('0') {
+            "" => "0".to_string(),
+            t if t.starts_with('.') => format!("0{}", t),
+            t => t.to_string(),
+        };
+        if negative && s != "0" {
+            Some(format!("-{}", s))
+        } else {
+            Some(s)
+        }
+    }
+
+    /// Whether the value is a whole number of any size, e.g. `1.0` or `1e400`,
+    /// decided from the digits without rounding through `f64`.
+    pub(crate) fn has_integer_value(&self) -> bool {
+        let (_, digits, point) = self.decompose();
+        let significant = digits.trim_end_matches('0').len() as i64;
+        significant == 0 || point >= significant
+    }
+
+    /// The decimal string when the value is an integer of reasonable size.
+    fn integer_string(&self) -> Option<String> {
+        if self.is_integer() {
+            return Some(self.0.clone());
+        }
+        let (_, digits, point) = self.decompose();
+        if digits.bytes().all(|b| b == b'0') {
+            return Some("0".to_string());
+        }
+        // a non-zero value below 1 in magnitude is never an integer
+        if point <= 0 || point > MAX_INTEGER_EXPONENT + digits.len() as i64 {
+            return None;
+        }
+        self.to_decimal_string().filter(|s| !s.contains('.'))
+    }
+
+    /// The sign, all significant digits, and where the decimal point goes
+    /// relative to the start of those digits.
+    fn decompose(&self) -> (bool, String, i64) {
+        let s = self.0.as_str();
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let (mantissa, exp) = match s.find(['e', 'E']) {
+            Some(i) => {
+                let exp = &s[i + 1..];
+                // an exponent beyond `i64` saturates towards its sign
+                let overflow = if exp.starts_with('-') {
+                    i64::MIN
+                } else {
+                    i64::MAX
+                };
+                (&s[..i], exp.parse::<i64>().unwrap_or(overflow))
+            }
+            None => (s, 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let point = (int.len() as i64).saturating_add(exp);
+        (negative, format!("{}{}", int, frac), point)
+    }
+}
+
+impl FromStr for Number {
+    type Err = InvalidNumber;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match super::winnow::is_number(s) {
+            true => Ok(Self(s.to_string())),
+            false => Err(InvalidNumber(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for Number {
+            fn from(v: $ty) -> Self {
+                Self(v.to_string())
+            }
+        })*
+    };
+}
+
+impl_from_integer!(i64, u64, i128, u128);
+
+/// How a number lexeme is stored without `lossless_numbers`: an `Integer`
+/// when it has no fraction or exponent and fits, a `Double` otherwise.
+/// `-0` is a `Double` so its sign survives.
+pub(crate) fn native<T>(lexeme: &str, integer: fn(i64) -> T, double: fn(f64) -> T) -> T {
+    if !lexeme.contains(['.', 'e', 'E']) && lexeme != "-0" {
+        if let Ok(v) = lexeme.parse() {
+            return integer(v);
+        }
+    }
+    double(lexeme.parse().unwrap_or(f64::NAN))
+}
+
+/// Order two numbers of any representation by value; `None` if either is
+/// not a number.
+pub(crate) fn compare_numbers(left: &JsonValue, right: &JsonValue) -> Option<Ordering> {
+    if let (Some(l), Some(r)) = (left.as_i64(), right.as_i64()) {
+        return Some(l.cmp(&r));
+    }
+    if let (Some(l), Some(r)) = (left.as_u64(), right.as_u64()) {
+        return Some(l.cmp(&r));
+    }
+    left.as_f64()?.partial_cmp(&right.as_f64()?)
+}
+
+impl JsonValue {
+    /// The value as an `i64`, for `Integer` and integral `Number`s.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Integer(v) => Some(*v),
+            JsonValue::Number(n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// The value as a `u64`, for non-negative `Integer` and integral `Number`s.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            JsonValue::Integer(v) => (*v).try_into().ok(),
+            JsonValue::Number(n) => n.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// The value of any number as an `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Integer(v) => Some(*v as f64),
+            JsonValue::Double(v) => Some(*v),
+            JsonValue::Number(n) => Some(n.as_f64()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_should_convert_exactly() {
+        let n = |s: &str| s.parse::<Number>().unwrap();
+        assert_eq!(n("9007199254740993").as_i64(), Some(9007199254740993));
+        assert_eq!(n("18446744073709551615").as_i64(), None);
+        assert_eq!(n("18446744073709551615").as_u64(), Some(u64::MAX));
+        assert_eq!(n("-1").as_u64(), None);
+        assert_eq!(n("1.0").as_i64(), Some(1));
+        assert_eq!(n("1.5").as_i64(), None);
+        assert_eq!(n("1e999999999").as_i64(), None);
+        assert_eq!(n("-0").as_f64().to_bits(), (-0.0f64).to_bits());
+        assert!(n("1e400").as_f64().is_infinite());
+
+        for (lexeme, decimal) in [
+            ("0", "0"),
+            ("-0", "0"),
+            ("-0.0e5", "0"),
+            ("0.10", "0.1"),
+            ("123.456e1", "1234.56"),
+            ("-1.250e2", "-125"),
+            ("1E-3", "0.001"),
+            ("12e-1", "1.2"),
+            ("5e2", "500"),
+            (
+                "123456789012345678901234567890.5",
+                "123456789012345678901234567890.5",
+            ),
+        ] {
+            assert_eq!(
+                n(lexeme).to_decimal_string().as_deref(),
+                Some(decimal),
+                "{}",
+                lexeme
+            );
+        }
+
+        // huge exponents are refused before any digits are expanded
+        for lexeme in [
+            "1e-99999999",
+            "1e-99999999999999999999",
+            "1e99999999999999999999",
+            "-1.5E+99999999999999999999",
+        ] {
+            assert_eq!(n(lexeme).as_i64(), None, "{}", lexeme);
+            assert_eq!(n(lexeme).to_decimal_string(), None, "{}", lexeme);
+        }
+        assert_eq!(n("0e-99999999999999999999").as_i64(), Some(0));
+        assert_eq!(n("-0.0e99999999999999999999").as_u64(), Some(0));
+
+        for invalid in ["", "-", "+1", "01", "1.", ".5", "1e", "NaN", "1 "] {
+            assert!(invalid.parse::<Number>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn native_should_pick_integer_or_double() {
+        let native = |lexeme| native(lexeme, JsonValue::Integer, JsonValue::Double);
+        assert_eq!(native("-12"), JsonValue::Integer(-12));
+        assert_eq!(native("1.0"), JsonValue::Double(1.0));
+        assert_eq!(
+            native("9223372036854775808"),
+            JsonValue::Double(9223372036854775808.0)
+        );
+        let JsonValue::Double(zero) = native("-0") else {
+            panic!("expected double");
+        };
+        assert!(zero.is_sign_negative());
+    }
+}
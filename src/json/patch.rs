@@ -0,0 +1,452 @@
+//! JSON Patch (RFC 6902) and JSON Merge Patch (RFC 7396).
+
+use thiserror::Error;
+
+use super::{path::values_equal, JsonError, JsonObject, JsonPointer, JsonValue, PointerError};
+
+/// A JSON Patch document: operations applied in order, all or nothing.
+///
+/// ```
+/// use grammar::json::{self, Patch};
+///
+/// let mut doc = json::parse(r#"{"name": "Jack", "marks": [90]}"#)?;
+/// let patch = Patch::parse(r#"[
+///     {"op": "test", "path": "/name", "value": "Jack"},
+///     {"op": "add", "path": "/marks/-", "value": 85}
+/// ]"#)?;
+/// doc.apply_patch(&patch)?;
+/// assert_eq!(doc.to_string(), r#"{"name":"Jack","marks":[90,85]}"#);
+/// # Ok::<(), grammar::json::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch(pub Vec<PatchOperation>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOperation {
+    Add {
+        path: JsonPointer,
+        value: JsonValue,
+    },
+    Remove {
+        path: JsonPointer,
+    },
+    Replace {
+        path: JsonPointer,
+        value: JsonValue,
+    },
+    Move {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Copy {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Test {
+        path: JsonPointer,
+        value: JsonValue,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PatchError {
+    #[error("Invalid patch operation {index}: {message}")]
+    InvalidOperation { index: usize, message: String },
+    #[error("Patch operation {index} ({op}) failed: {source}")]
+    Pointer {
+        index: usize,
+        op: &'static str,
+        source: PointerError,
+    },
+    #[error(
+        "Patch operation {index} (test) failed: value at {path:?} is {actual}, expected {expected}"
+    )]
+    TestFailed {
+        index: usize,
+        path: String,
+        expected: Box<JsonValue>,
+        actual: Box<JsonValue>,
+    },
+    #[error("Patch operation {index} (move) cannot move {from:?} into its own child {path:?}")]
+    MoveIntoChild {
+        index: usize,
+        from: String,
+        path: String,
+    },
+}
+
+impl Patch {
+    /// Parse a patch document, e.g. `[{"op": "remove", "path": "/a"}]`.
+    pub fn parse(input: &str) -> Result<Self, JsonError> {
+        Ok(Self::from_value(&super::parse(input)?)?)
+    }
+
+    pub fn from_value(value: &JsonValue) -> Result<Self, PatchError> {
+        let JsonValue::Array(ops) = value else {
+            return Err(PatchError::InvalidOperation {
+                index: 0,
+                message: format!("a patch must be an array, found {}", value.kind()),
+            });
+        };
+        ops.iter()
+            .enumerate()
+            .map(|(index, op)| PatchOperation::from_value(index, op))
+            .collect::<Result<_, _>>()
+            .map(Patch)
+    }
+
+    /// The patch as a JSON document.
+    pub fn to_value(&self) -> JsonValue {
+        JsonValue::Array(self.0.iter().map(PatchOperation::to_value).collect())
+    }
+
+    /// Apply every operation to `value`. If one fails, `value` is left as it
+    /// was and the error names the failing operation.
+    pub fn apply(&self, value: &mut JsonValue) -> Result<(), PatchError> {
+        let mut patched = value.clone();
+        for (index, op) in self.0.iter().enumerate() {
+            op.apply(index, &mut patched)?;
+        }
+        *value = patched;
+        Ok(())
+    }
+}
+
+impl PatchOperation {
+    /// The `op` member, e.g. `add`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+            PatchOperation::Move { .. } => "move",
+            PatchOperation::Copy { .. } => "copy",
+            PatchOperation::Test { .. } => "test",
+        }
+    }
+
+    fn from_value(index: usize, value: &JsonValue) -> Result<Self, PatchError> {
+        let invalid = |message: String| PatchError::InvalidOperation { index, message };
+        let JsonValue::Object(obj) = value else {
+            return Err(invalid(format!(
+                "an operation must be an object, found {}",
+                value.kind()
+            )));
+        };
+        let member = |name: &str| {
+            obj.get(name)
+                .ok_or_else(|| invalid(format!("missing member {:?}", name)))
+        };
+        let pointer = |name: &str| match member(name)? {
+            JsonValue::String(s) => JsonPointer::parse(s).map_err(|e| invalid(e.to_string())),
+            v => Err(invalid(format!(
+                "member {:?} must be a string, found {}",
+                name,
+                v.kind()
+            ))),
+        };
+        let op = match member("op")? {
+            JsonValue::String(op) => op.as_str(),
+            v => {
+                return Err(invalid(format!(
+                    "member \"op\" must be a string, found {}",
+                    v.kind()
+                )))
+            }
+        };
+        let path = pointer("path")?;
+        Ok(match op {
+            "add" => PatchOperation::Add {
+                path,
+                value: member("value")?.clone(),
+            },
+            "remove" => PatchOperation::Remove { path },
+            "replace" => PatchOperation::Replace {
+                path,
+                value: member("value")?.clone(),
+            },
+            "move" => PatchOperation::Move {
+                from: pointer("from")?,
+                path,
+            },
+            "copy" => PatchOperation::Copy {
+                from: pointer("from")?,
+                path,
+            },
+            "test" => PatchOperation::Test {
+                path,
+                value: member("value")?.clone(),
+            },
+            op => return Err(invalid(format!("unknown op {:?}", op))),
+        })
+    }
+
+    fn to_value(&self) -> JsonValue {
+        let mut obj = JsonObject::new();
+        obj.insert("op".to_string(), JsonValue::String(self.name().to_string()));
+        let (path, from, value) = match self {
+            PatchOperation::Remove { path } => (path, None, None),
+            PatchOperation::Add { path, value }
+            | PatchOperation::Replace { path, value }
+            | PatchOperation::Test { path, value } => (path, None, Some(value)),
+            PatchOperation::Move { from, path } | PatchOperation::Copy { from, path } => {
+                (path, Some(from), None)
+            }
+        };
+        if let Some(from) = from {
+            obj.insert("from".to_string(), JsonValue::String(from.to_string()));
+        }
+        obj.insert("path".to_string(), JsonValue::String(path.to_string()));
+        if let Some(value) = value {
+            obj.insert("value".to_string(), value.clone());
+        }
+        JsonValue::Object(obj)
+    }
+
+    fn apply(&self, index: usize, target: &mut JsonValue) -> Result<(), PatchError> {
+        let op = self.name();
+        let pointer_error = |source| PatchError::Pointer { index, op, source };
+        match self {
+            PatchOperation::Add { path, value } => {
+                path.insert(target, value.clone()).map_err(pointer_error)?;
+            }
+            PatchOperation::Remove { path } => {
+                path.remove(target).map_err(pointer_error)?;
+            }
+            PatchOperation::Replace { path, value } => {
+                *path.get_mut(target).map_err(pointer_error)? = value.clone();
+            }
+            PatchOperation::Move { from, path } => {
+                if from == path {
+                    // still has to exist
+                    from.get(target).map_err(pointer_error)?;
+                    return Ok(());
+                }
+                if path.tokens().starts_with(from.tokens()) {
+                    return Err(PatchError::MoveIntoChild {
+                        index,
+                        from: from.to_string(),
+                        path: path.to_string(),
+                    });
+                }
+                let value = from.remove(target).map_err(pointer_error)?;
+                path.insert(target, value).map_err(pointer_error)?;
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = from.get(target).map_err(pointer_error)?.clone();
+                path.insert(target, value).map_err(pointer_error)?;
+            }
+            PatchOperation::Test { path, value } => {
+                let actual = path.get(target).map_err(pointer_error)?;
+                if !values_equal(actual, value) {
+                    return Err(PatchError::TestFailed {
+                        index,
+                        path: path.to_string(),
+                        expected: Box::new(value.clone()),
+                        actual: Box::new(actual.clone()),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl JsonValue {
+    /// See [`Patch::apply`].
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), PatchError> {
+        patch.apply(self)
+    }
+
+    /// Apply a JSON Merge Patch: object members in `patch` replace or, when
+    /// `null`, remove members of `self`; any other patch replaces `self`.
+    pub fn merge_patch(&mut self, patch: &JsonValue) {
+        let JsonValue::Object(members) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, JsonValue::Object(_)) {
+            *self = JsonValue::Object(JsonObject::new());
+        }
+        let JsonValue::Object(obj) = self else {
+            unreachable!()
+        };
+        for (key, value) in members {
+            if *value == JsonValue::Null {
+                obj.shift_remove(key);
+            } else {
+                obj.entry(key.clone())
+                    .or_insert(JsonValue::Null)
+                    .merge_patch(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse;
+
+    fn patched(doc: &str, patch: &str) -> Result<JsonValue, PatchError> {
+        let mut doc = parse(doc).unwrap();
+        doc.apply_patch(&Patch::parse(patch).unwrap())?;
+        Ok(doc)
+    }
+
+    #[test]
+    fn apply_patch_should_follow_rfc_examples() {
+        for (doc, patch, expected) in [
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                r#"{"foo": "bar", "baz": "qux"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "baz"]}"#,
+                r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "remove", "path": "/baz"}]"#,
+                r#"{"foo": "bar"}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+                r#"{"baz": "boo", "foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+            ),
+            (
+                r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+            ),
+            (
+                r#"{"foo": {"bar": [1]}}"#,
+                r#"[{"op": "copy", "from": "/foo/bar", "path": "/baz"}]"#,
+                r#"{"foo": {"bar": [1]}, "baz": [1]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+                r#"[{"op": "test", "path": "/baz", "value": "qux"},
+                    {"op": "test", "path": "/foo/1", "value": 2.0}]"#,
+                r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            ),
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/child", "value": {"grandchild": [1]}}]"#,
+                r#"{"foo": "bar", "child": {"grandchild": [1]}}"#,
+            ),
+            (
+                r#"{"a~b": 1}"#,
+                r#"[{"op": "replace", "path": "", "value": [1]}]"#,
+                r#"[1]"#,
+            ),
+        ] {
+            assert_eq!(
+                patched(doc, patch).unwrap(),
+                parse(expected).unwrap(),
+                "{}",
+                patch
+            );
+        }
+    }
+
+    #[test]
+    fn apply_patch_should_be_atomic() {
+        let doc = r#"{"foo": "bar", "list": [1]}"#;
+        let mut value = parse(doc).unwrap();
+        let patch = Patch::parse(
+            r#"[
+                {"op": "add", "path": "/baz", "value": "qux"},
+                {"op": "test", "path": "/foo", "value": "baz"}
+            ]"#,
+        )
+        .unwrap();
+        let err = value.apply_patch(&patch).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Patch operation 1 (test) failed: value at "/foo" is "bar", expected "baz""#
+        );
+        assert_eq!(value, parse(doc).unwrap());
+
+        for (patch, expected) in [
+            (
+                r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#,
+                r#"Patch operation 0 (add) failed: No member "baz" in the object at """#,
+            ),
+            (
+                r#"[{"op": "remove", "path": "/list/1"}]"#,
+                r#"Patch operation 0 (remove) failed: Index 1 is out of range for the array of length 1 at "/list""#,
+            ),
+            (
+                r#"[{"op": "move", "from": "/list", "path": "/list/0"}]"#,
+                r#"Patch operation 0 (move) cannot move "/list" into its own child "/list/0""#,
+            ),
+        ] {
+            assert_eq!(patched(doc, patch).unwrap_err().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn patch_should_round_trip_through_json() {
+        let input =
+            r#"[{"op":"move","from":"/a","path":"/b"},{"op":"test","path":"/c","value":[1]}]"#;
+        let patch = Patch::parse(input).unwrap();
+        assert_eq!(patch.0[0].name(), "move");
+        assert_eq!(patch.to_value().to_string(), input);
+
+        for (input, message) in [
+            (r#"{"op": "add"}"#, "a patch must be an array, found object"),
+            (
+                r#"[{"op": "add", "path": "/a"}]"#,
+                "missing member \"value\"",
+            ),
+            (r#"[{"op": "nop", "path": ""}]"#, "unknown op \"nop\""),
+            (
+                r#"[{"op": "remove", "path": "a"}]"#,
+                "must be empty or start with `/`",
+            ),
+        ] {
+            let err = Patch::parse(input).unwrap_err().to_string();
+            assert!(err.contains(message), "{}", err);
+        }
+    }
+
+    #[test]
+    fn merge_patch_should_follow_rfc_examples() {
+        for (target, patch, expected) in [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (
+                r#"{"x":1}"#,
+                r#"{"a":{"bb":{"ccc":null}}}"#,
+                r#"{"x":1,"a":{"bb":{}}}"#,
+            ),
+        ] {
+            let mut value = parse(target).unwrap();
+            value.merge_patch(&parse(patch).unwrap());
+            assert_eq!(value.to_string(), expected, "{} + {}", target, patch);
+        }
+    }
+}
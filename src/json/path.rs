@@ -0,0 +1,897 @@
+//! JSONPath (RFC 9535) queries over [`JsonValue`].
+//!
+//! The query syntax is defined in `jsonpath.pest`; a query is compiled once
+//! into a [`JsonPath`] and can then be run against any number of documents.
+
+use std::{borrow::Cow, cmp::Ordering, fmt, str::FromStr};
+
+use pest::{
+    error::{ErrorVariant, InputLocation},
+    iterators::Pair,
+    Parser, Span,
+};
+use regex::Regex;
+
+use super::{number::compare_numbers, JsonError, JsonPointer, JsonValue, ParseError, PathSegment};
+
+#[derive(Debug, pest_derive::Parser)]
+#[grammar = "json/jsonpath.pest"]
+struct JsonPathParser;
+
+/// A compiled JSONPath query, e.g. `$.marks[?@ > 85]` or `$..city`.
+///
+/// ```
+/// use grammar::json::{self, JsonPath};
+///
+/// let doc = json::parse(r#"{"name": "Jack", "marks": [90, 80, 95]}"#)?;
+/// let path = JsonPath::parse("$.marks[?@ > 85]")?;
+/// let paths: Vec<_> = path.query(&doc).iter().map(|node| node.path.to_string()).collect();
+/// assert_eq!(paths, ["$['marks'][0]", "$['marks'][2]"]);
+/// # Ok::<(), grammar::json::JsonError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+/// A value matched by a query, and where in the document it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathNode<'a> {
+    pub path: NormalizedPath,
+    pub value: &'a JsonValue,
+}
+
+/// The location of a single node, rendered as `$['marks'][0]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizedPath(Vec<PathSegment>);
+
+#[derive(Debug, Clone)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(LogicalExpr),
+}
+
+#[derive(Debug, Clone)]
+enum LogicalExpr {
+    Or(Vec<LogicalExpr>),
+    And(Vec<LogicalExpr>),
+    Not(Box<LogicalExpr>),
+    Compare(Comparable, CompareOp, Comparable),
+    Exists(Query),
+    Function(Function),
+}
+
+/// `$...` or `@...` inside a filter.
+#[derive(Debug, Clone)]
+struct Query {
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+/// An operand of a comparison or a function argument.
+#[derive(Debug, Clone)]
+enum Comparable {
+    Literal(JsonValue),
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+    kind: FunctionKind,
+    args: Vec<Comparable>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionKind {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+/// The declared type of a function parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamType {
+    Value,
+    Nodes,
+}
+
+/// The largest integer the RFC allows in indexes and slices (I-JSON range).
+const MAX_INT: i64 = (1 << 53) - 1;
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<Self, JsonError> {
+        let pair = JsonPathParser::parse(Rule::jsonpath, query)
+            .map_err(|e| JsonError::Path(Box::new(to_parse_error(query, e))))?
+            .next()
+            .expect("the grammar guarantees a root query");
+        Ok(Self {
+            segments: parse_query(pair)?.segments,
+        })
+    }
+
+    /// Every node the query selects, in document order for each segment.
+    pub fn query<'a>(&self, value: &'a JsonValue) -> Vec<PathNode<'a>> {
+        select(&self.segments, value, value)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = JsonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl JsonValue {
+    /// Compile a JSONPath query and run it against this value.
+    pub fn query(&self, query: &str) -> Result<Vec<PathNode<'_>>, JsonError> {
+        Ok(JsonPath::parse(query)?.query(self))
+    }
+}
+
+impl NormalizedPath {
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// The same location as a JSON Pointer, e.g. `/marks/0`.
+    pub fn to_pointer(&self) -> JsonPointer {
+        let mut pointer = JsonPointer::root();
+        for segment in &self.0 {
+            match segment {
+                PathSegment::Key(key) => pointer.push(key.as_str()),
+                PathSegment::Index(index) => pointer.push(index.to_string()),
+            }
+        }
+        pointer
+    }
+
+    fn join(&self, segment: PathSegment) -> Self {
+        let mut path = self.clone();
+        path.0.push(segment);
+        path
+    }
+}
+
+impl fmt::Display for NormalizedPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "$")?;
+        for segment in &self.0 {
+            match segment {
+                PathSegment::Key(key) => {
+                    write!(f, "['")?;
+                    for c in key.chars() {
+                        match c {
+                            '\u{08}' => write!(f, "\\b")?,
+                            '\u{0c}' => write!(f, "\\f")?,
+                            '\n' => write!(f, "\\n")?,
+                            '\r' => write!(f, "\\r")?,
+                            '\t' => write!(f, "\\t")?,
+                            '\'' | '\\' => write!(f, "\\{}", c)?,
+                            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+                            c => write!(f, "{}", c)?,
+                        }
+                    }
+                    write!(f, "']")?;
+                }
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn select<'a>(
+    segments: &[Segment],
+    root: &'a JsonValue,
+    start: &'a JsonValue,
+) -> Vec<PathNode<'a>> {
+    let mut nodes = vec![PathNode {
+        path: NormalizedPath::default(),
+        value: start,
+    }];
+    for segment in segments {
+        let mut next = Vec::new();
+        for node in nodes {
+            if segment.descendant {
+                let mut descendants = vec![node];
+                collect_descendants(&mut descendants, 0);
+                for node in &descendants {
+                    segment.apply(node, root, &mut next);
+                }
+            } else {
+                segment.apply(&node, root, &mut next);
+            }
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+/// Append every descendant of `nodes[index]` in pre-order.
+fn collect_descendants<'a>(nodes: &mut Vec<PathNode<'a>>, index: usize) {
+    let children: Vec<_> = children(&nodes[index]).collect();
+    for child in children {
+        nodes.push(child);
+        collect_descendants(nodes, nodes.len() - 1);
+    }
+}
+
+fn children<'a, 'n>(node: &'n PathNode<'a>) -> Box<dyn Iterator<Item = PathNode<'a>> + 'n> {
+    match node.value {
+        JsonValue::Array(values) => {
+            Box::new(values.iter().enumerate().map(|(i, value)| PathNode {
+                path: node.path.join(PathSegment::Index(i)),
+                value,
+            }))
+        }
+        JsonValue::Object(obj) => Box::new(obj.iter().map(|(key, value)| PathNode {
+            path: node.path.join(PathSegment::Key(key.clone())),
+            value,
+        })),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+impl Segment {
+    fn apply<'a>(&self, node: &PathNode<'a>, root: &'a JsonValue, out: &mut Vec<PathNode<'a>>) {
+        for selector in &self.selectors {
+            selector.apply(node, root, out);
+        }
+    }
+
+    /// Whether the segment selects at most one node, as comparisons require.
+    fn is_singular(&self) -> bool {
+        !self.descendant
+            && matches!(
+                self.selectors.as_slice(),
+                [Selector::Name(_) | Selector::Index(_)]
+            )
+    }
+}
+
+impl Selector {
+    fn apply<'a>(&self, node: &PathNode<'a>, root: &'a JsonValue, out: &mut Vec<PathNode<'a>>) {
+        match (self, node.value) {
+            (Selector::Name(name), JsonValue::Object(obj)) => {
+                if let Some(value) = obj.get(name) {
+                    out.push(PathNode {
+                        path: node.path.join(PathSegment::Key(name.clone())),
+                        value,
+                    });
+                }
+            }
+            (Selector::Wildcard, _) => out.extend(children(node)),
+            (Selector::Index(index), JsonValue::Array(values)) => {
+                let len = values.len() as i64;
+                let index = if *index < 0 { len + index } else { *index };
+                if (0..len).contains(&index) {
+                    let index = index as usize;
+                    out.push(PathNode {
+                        path: node.path.join(PathSegment::Index(index)),
+                        value: &values[index],
+                    });
+                }
+            }
+            (Selector::Slice { start, end, step }, JsonValue::Array(values)) => {
+                for index in slice_indexes(values.len() as i64, *start, *end, step.unwrap_or(1)) {
+                    out.push(PathNode {
+                        path: node.path.join(PathSegment::Index(index)),
+                        value: &values[index],
+                    });
+                }
+            }
+            (Selector::Filter(expr), JsonValue::Array(_) | JsonValue::Object(_)) => {
+                out.extend(children(node).filter(|child| expr.test(child.value, root)))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The indexes selected by `[start:end:step]`, following RFC 9535 section 2.3.4.2.2.
+fn slice_indexes(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let normalize = |i: i64| if i < 0 { len + i } else { i };
+    let mut indexes = Vec::new();
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indexes.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = end.map_or(-1, |end| normalize(end).clamp(-1, len - 1));
+        let mut i = upper;
+        while lower < i {
+            indexes.push(i as usize);
+            i += step;
+        }
+    }
+    indexes
+}
+
+impl LogicalExpr {
+    fn test(&self, current: &JsonValue, root: &JsonValue) -> bool {
+        match self {
+            LogicalExpr::Or(exprs) => exprs.iter().any(|expr| expr.test(current, root)),
+            LogicalExpr::And(exprs) => exprs.iter().all(|expr| expr.test(current, root)),
+            LogicalExpr::Not(expr) => !expr.test(current, root),
+            LogicalExpr::Compare(left, op, right) => {
+                let left = left.value(current, root);
+                let right = right.value(current, root);
+                op.compare(left.as_deref(), right.as_deref())
+            }
+            LogicalExpr::Exists(query) => !query.select(current, root).is_empty(),
+            LogicalExpr::Function(function) => function.test(current, root),
+        }
+    }
+}
+
+impl Query {
+    fn select<'a>(&self, current: &'a JsonValue, root: &'a JsonValue) -> Vec<PathNode<'a>> {
+        let start = if self.absolute { root } else { current };
+        select(&self.segments, root, start)
+    }
+
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(Segment::is_singular)
+    }
+}
+
+impl Comparable {
+    /// The value of the operand, `None` when a query selects nothing.
+    fn value<'a>(
+        &'a self,
+        current: &'a JsonValue,
+        root: &'a JsonValue,
+    ) -> Option<Cow<'a, JsonValue>> {
+        match self {
+            Comparable::Literal(value) => Some(Cow::Borrowed(value)),
+            Comparable::Query(query) => query
+                .select(current, root)
+                .into_iter()
+                .next()
+                .map(|node| Cow::Borrowed(node.value)),
+            Comparable::Function(function) => function.value(current, root),
+        }
+    }
+
+    fn nodes<'a>(&'a self, current: &'a JsonValue, root: &'a JsonValue) -> Vec<PathNode<'a>> {
+        match self {
+            Comparable::Query(query) => query.select(current, root),
+            // rejected when the query is compiled
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl CompareOp {
+    fn compare(self, left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+        match self {
+            CompareOp::Eq => equal(left, right),
+            CompareOp::Ne => !equal(left, right),
+            CompareOp::Lt => less(left, right),
+            CompareOp::Le => less(left, right) || equal(left, right),
+            CompareOp::Gt => less(right, left),
+            CompareOp::Ge => less(right, left) || equal(left, right),
+        }
+    }
+}
+
+/// Two operands are equal when both are empty or both hold equal values.
+fn equal(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => values_equal(left, right),
+        _ => false,
+    }
+}
+
+/// JSON equality: numbers compare by value, so `1 == 1.0`, and object
+/// members compare regardless of their order.
+pub(crate) fn values_equal(left: &JsonValue, right: &JsonValue) -> bool {
+    match (left, right) {
+        (JsonValue::Array(left), JsonValue::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| values_equal(l, r))
+        }
+        (JsonValue::Object(left), JsonValue::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, l)| right.get(key).is_some_and(|r| values_equal(l, r)))
+        }
+        _ => match compare_numbers(left, right) {
+            Some(ordering) => ordering == Ordering::Equal,
+            None => left == right,
+        },
+    }
+}
+
+/// Only numbers and strings are ordered; anything else is never less.
+fn less(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (Some(JsonValue::String(left)), Some(JsonValue::String(right))) => left < right,
+        (Some(left), Some(right)) => compare_numbers(left, right) == Some(Ordering::Less),
+        _ => false,
+    }
+}
+
+impl Function {
+    fn value<'a>(
+        &'a self,
+        current: &'a JsonValue,
+        root: &'a JsonValue,
+    ) -> Option<Cow<'a, JsonValue>> {
+        let len = match self.kind {
+            FunctionKind::Length => match self.args[0].value(current, root)?.as_ref() {
+                JsonValue::String(s) => s.chars().count(),
+                JsonValue::Array(values) => values.len(),
+                JsonValue::Object(obj) => obj.len(),
+                _ => return None,
+            },
+            FunctionKind::Count => self.args[0].nodes(current, root).len(),
+            FunctionKind::Value => {
+                let nodes = self.args[0].nodes(current, root);
+                return match nodes.as_slice() {
+                    [node] => Some(Cow::Borrowed(node.value)),
+                    _ => None,
+                };
+            }
+            // rejected when the query is compiled
+            FunctionKind::Match | FunctionKind::Search => return None,
+        };
+        Some(Cow::Owned(JsonValue::Integer(len as i64)))
+    }
+
+    fn test(&self, current: &JsonValue, root: &JsonValue) -> bool {
+        let (Some(s), Some(pattern)) = (
+            self.args[0].value(current, root),
+            self.args[1].value(current, root),
+        ) else {
+            return false;
+        };
+        let (JsonValue::String(s), JsonValue::String(pattern)) = (s.as_ref(), pattern.as_ref())
+        else {
+            return false;
+        };
+        let pattern = match self.kind {
+            FunctionKind::Match => format!(r"\A(?:{})\z", pattern),
+            _ => pattern.clone(),
+        };
+        // an invalid pattern matches nothing, as the RFC requires
+        Regex::new(&pattern).is_ok_and(|re| re.is_match(s))
+    }
+}
+
+impl FunctionKind {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "length" => FunctionKind::Length,
+            "count" => FunctionKind::Count,
+            "match" => FunctionKind::Match,
+            "search" => FunctionKind::Search,
+            "value" => FunctionKind::Value,
+            _ => return None,
+        })
+    }
+
+    fn params(self) -> &'static [ParamType] {
+        match self {
+            FunctionKind::Length => &[ParamType::Value],
+            FunctionKind::Count | FunctionKind::Value => &[ParamType::Nodes],
+            FunctionKind::Match | FunctionKind::Search => &[ParamType::Value, ParamType::Value],
+        }
+    }
+
+    /// Whether the result is a logical value rather than a JSON value.
+    fn is_logical(self) -> bool {
+        matches!(self, FunctionKind::Match | FunctionKind::Search)
+    }
+}
+
+fn to_parse_error(input: &str, e: pest::error::Error<Rule>) -> ParseError {
+    let offset = match e.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    };
+    let err = ParseError::new(input, offset);
+    match e.variant {
+        ErrorVariant::ParsingError { positives, .. } => {
+            err.with_expected(positives.into_iter().map(describe_rule))
+        }
+        ErrorVariant::CustomError { message } => err.with_message(message),
+    }
+}
+
+fn describe_rule(rule: Rule) -> &'static str {
+    match rule {
+        Rule::root_query => "`$`",
+        Rule::current_query => "`@`",
+        Rule::descendant_segment | Rule::child_segment => "segment",
+        Rule::name | Rule::literal_string => "string",
+        Rule::member_name => "member name",
+        Rule::wildcard => "`*`",
+        Rule::index | Rule::int | Rule::slice_start | Rule::slice_end | Rule::slice_step => {
+            "integer"
+        }
+        Rule::slice => "slice",
+        Rule::filter => "filter",
+        Rule::number => "number",
+        Rule::logical_or | Rule::logical_and | Rule::paren_expr | Rule::test_expr => "expression",
+        Rule::comparison => "comparison",
+        Rule::comparison_op => "comparison operator",
+        Rule::function_expr | Rule::function_name => "function",
+        Rule::not => "`!`",
+        Rule::true_lit | Rule::false_lit | Rule::null_lit => "literal",
+        Rule::EOI => "end of query",
+        _ => "token",
+    }
+}
+
+/// An error for a query the grammar accepted but that is not well formed.
+fn invalid(span: Span, message: impl Into<String>) -> JsonError {
+    let err = ParseError::new(span.get_input(), span.start()).with_message(message);
+    JsonError::Path(Box::new(err))
+}
+
+fn parse_query(pair: Pair<Rule>) -> Result<Query, JsonError> {
+    Ok(Query {
+        absolute: pair.as_rule() == Rule::root_query,
+        segments: pair
+            .into_inner()
+            .map(parse_segment)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn parse_segment(pair: Pair<Rule>) -> Result<Segment, JsonError> {
+    Ok(Segment {
+        descendant: pair.as_rule() == Rule::descendant_segment,
+        selectors: pair
+            .into_inner()
+            .map(parse_selector)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn parse_selector(pair: Pair<Rule>) -> Result<Selector, JsonError> {
+    match pair.as_rule() {
+        Rule::name => Ok(Selector::Name(parse_string(pair)?)),
+        Rule::member_name => Ok(Selector::Name(pair.as_str().to_string())),
+        Rule::wildcard => Ok(Selector::Wildcard),
+        Rule::index => Ok(Selector::Index(parse_int(&pair)?)),
+        Rule::slice => {
+            let (mut start, mut end, mut step) = (None, None, None);
+            for part in pair.into_inner() {
+                let value = Some(parse_int(&part)?);
+                match part.as_rule() {
+                    Rule::slice_start => start = value,
+                    Rule::slice_end => end = value,
+                    _ => step = value,
+                }
+            }
+            Ok(Selector::Slice { start, end, step })
+        }
+        Rule::filter => Ok(Selector::Filter(parse_logical(first_inner(pair))?)),
+        _ => unreachable!(),
+    }
+}
+
+fn first_inner(pair: Pair<Rule>) -> Pair<Rule> {
+    pair.into_inner()
+        .next()
+        .expect("the grammar guarantees an inner pair")
+}
+
+/// Decode a `name` or `literal_string`.
+fn parse_string(pair: Pair<Rule>) -> Result<String, JsonError> {
+    let chars = first_inner(pair);
+    super::pest::unescape(chars.as_str()).map_err(|message| invalid(chars.as_span(), message))
+}
+
+/// The integer of an index or slice part, which must be within ±(2^53 - 1).
+fn parse_int(pair: &Pair<Rule>) -> Result<i64, JsonError> {
+    pair.as_str()
+        .parse()
+        .ok()
+        .filter(|v: &i64| v.unsigned_abs() <= MAX_INT as u64)
+        .ok_or_else(|| invalid(pair.as_span(), "integer out of range"))
+}
+
+fn parse_logical(pair: Pair<Rule>) -> Result<LogicalExpr, JsonError> {
+    match pair.as_rule() {
+        Rule::logical_or | Rule::logical_and => {
+            let is_or = pair.as_rule() == Rule::logical_or;
+            let mut exprs = pair
+                .into_inner()
+                .map(parse_logical)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(match exprs.len() {
+                1 => exprs.remove(0),
+                _ if is_or => LogicalExpr::Or(exprs),
+                _ => LogicalExpr::And(exprs),
+            })
+        }
+        Rule::paren_expr | Rule::test_expr => {
+            let mut inner = pair.into_inner().peekable();
+            let negated = inner.next_if(|p| p.as_rule() == Rule::not).is_some();
+            let inner = inner.next().expect("the grammar guarantees an expression");
+            let expr = match inner.as_rule() {
+                Rule::logical_or => parse_logical(inner)?,
+                Rule::function_expr => {
+                    let span = inner.as_span();
+                    let function = parse_function(inner)?;
+                    if !function.kind.is_logical() {
+                        return Err(invalid(span, "function result must be compared"));
+                    }
+                    LogicalExpr::Function(function)
+                }
+                _ => LogicalExpr::Exists(parse_query(inner)?),
+            };
+            Ok(match negated {
+                true => LogicalExpr::Not(Box::new(expr)),
+                false => expr,
+            })
+        }
+        Rule::comparison => {
+            let mut inner = pair.into_inner();
+            let (Some(left), Some(op), Some(right)) = (inner.next(), inner.next(), inner.next())
+            else {
+                unreachable!("the grammar guarantees both operands")
+            };
+            let op = match op.as_str() {
+                "==" => CompareOp::Eq,
+                "!=" => CompareOp::Ne,
+                "<" => CompareOp::Lt,
+                "<=" => CompareOp::Le,
+                ">" => CompareOp::Gt,
+                _ => CompareOp::Ge,
+            };
+            Ok(LogicalExpr::Compare(
+                parse_comparable(left, ParamType::Value)?,
+                op,
+                parse_comparable(right, ParamType::Value)?,
+            ))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Parse a comparison operand or function argument and check it has the
+/// expected type: values need a literal, a singular query or a value function.
+fn parse_comparable(pair: Pair<Rule>, expected: ParamType) -> Result<Comparable, JsonError> {
+    let span = pair.as_span();
+    let comparable = match pair.as_rule() {
+        Rule::number => Comparable::Literal(parse_number(&pair)?),
+        Rule::literal_string => Comparable::Literal(JsonValue::String(parse_string(pair)?)),
+        Rule::true_lit => Comparable::Literal(JsonValue::Bool(true)),
+        Rule::false_lit => Comparable::Literal(JsonValue::Bool(false)),
+        Rule::null_lit => Comparable::Literal(JsonValue::Null),
+        Rule::root_query | Rule::current_query => {
+            let query = parse_query(pair)?;
+            if expected == ParamType::Value && !query.is_singular() {
+                return Err(invalid(span, "query must select a single node"));
+            }
+            return Ok(Comparable::Query(query));
+        }
+        Rule::function_expr => {
+            let function = parse_function(pair)?;
+            if function.kind.is_logical() {
+                return Err(invalid(span, "function result cannot be compared"));
+            }
+            Comparable::Function(function)
+        }
+        _ => unreachable!(),
+    };
+    match expected {
+        ParamType::Value => Ok(comparable),
+        ParamType::Nodes => Err(invalid(span, "expected a query")),
+    }
+}
+
+fn parse_number(pair: &Pair<Rule>) -> Result<JsonValue, JsonError> {
+    let s = pair.as_str();
+    if !s.contains(['.', 'e', 'E']) {
+        if let Ok(v) = s.parse() {
+            return Ok(JsonValue::Integer(v));
+        }
+    }
+    s.parse()
+        .map(JsonValue::Double)
+        .map_err(|e| invalid(pair.as_span(), e.to_string()))
+}
+
+fn parse_function(pair: Pair<Rule>) -> Result<Function, JsonError> {
+    let span = pair.as_span();
+    let mut inner = pair.into_inner();
+    let name = inner
+        .next()
+        .expect("the grammar guarantees a function name");
+    let kind = FunctionKind::parse(name.as_str()).ok_or_else(|| {
+        invalid(
+            name.as_span(),
+            format!("unknown function `{}`", name.as_str()),
+        )
+    })?;
+    let args: Vec<_> = inner.collect();
+    let params = kind.params();
+    if args.len() != params.len() {
+        return Err(invalid(
+            span,
+            format!(
+                "function `{}` takes {} argument(s), found {}",
+                name.as_str(),
+                params.len(),
+                args.len()
+            ),
+        ));
+    }
+    let args = args
+        .into_iter()
+        .zip(params)
+        .map(|(arg, param)| parse_comparable(arg, *param))
+        .collect::<Result<_, _>>()?;
+    Ok(Function { kind, args })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse;
+
+    // the bookstore example from RFC 9535, section 1.5
+    const STORE: &str = r#"{ "store": {
+        "book": [
+          { "category": "reference", "author": "Nigel Rees",
+            "title": "Sayings of the Century", "price": 8.95 },
+          { "category": "fiction", "author": "Evelyn Waugh",
+            "title": "Sword of Honour", "price": 12.99 },
+          { "category": "fiction", "author": "Herman Melville",
+            "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99 },
+          { "category": "fiction", "author": "J. R. R. Tolkien",
+            "title": "The Lord of the Rings", "isbn": "0-395-19395-8",
+            "price": 22.99 }
+        ],
+        "bicycle": { "color": "red", "price": 399 }
+    } }"#;
+
+    fn paths(doc: &JsonValue, query: &str) -> Vec<String> {
+        doc.query(query)
+            .unwrap()
+            .iter()
+            .map(|node| node.path.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn query_should_follow_rfc_examples() {
+        let doc = parse(STORE).unwrap();
+        let authors: Vec<_> = doc
+            .query("$.store.book[*].author")
+            .unwrap()
+            .into_iter()
+            .map(|node| node.value.clone())
+            .collect();
+        assert_eq!(authors.len(), 4);
+        assert_eq!(authors[3], JsonValue::String("J. R. R. Tolkien".into()));
+        assert_eq!(paths(&doc, "$..author").len(), 4);
+        assert_eq!(
+            paths(&doc, "$.store.*"),
+            ["$['store']['book']", "$['store']['bicycle']"]
+        );
+        assert_eq!(paths(&doc, "$.store..price").len(), 5);
+        assert_eq!(paths(&doc, "$..book[2]"), ["$['store']['book'][2]"]);
+        assert_eq!(paths(&doc, "$..book[-1]"), ["$['store']['book'][3]"]);
+        assert_eq!(paths(&doc, "$..book[0,1]"), paths(&doc, "$..book[:2]"),);
+        assert_eq!(
+            paths(&doc, "$..book[?@.isbn]"),
+            ["$['store']['book'][2]", "$['store']['book'][3]"]
+        );
+        assert_eq!(
+            paths(&doc, "$..book[?@.price<10]"),
+            ["$['store']['book'][0]", "$['store']['book'][2]"]
+        );
+        assert_eq!(paths(&doc, "$..*").len(), 27);
+    }
+
+    #[test]
+    fn query_should_filter_and_slice() {
+        let doc =
+            parse(r#"{"marks": [90, 80.5, 95, 70], "name": "Jack", "tags": ["a", "bc"]}"#).unwrap();
+        assert_eq!(
+            paths(&doc, "$.marks[?(@ > 85)]"),
+            ["$['marks'][0]", "$['marks'][2]"]
+        );
+        assert_eq!(paths(&doc, "$.marks[?@ == 80.5 || @ == 70]").len(), 2);
+        assert_eq!(paths(&doc, "$.marks[?!(@ >= 90)]").len(), 2);
+        assert_eq!(paths(&doc, "$.marks[?@ == 90.0]"), ["$['marks'][0]"]);
+        assert_eq!(
+            paths(&doc, "$.marks[::-2]"),
+            ["$['marks'][3]", "$['marks'][1]"]
+        );
+        assert_eq!(paths(&doc, "$.marks[1:-1]").len(), 2);
+        assert_eq!(paths(&doc, "$.marks[0:4:0]").len(), 0);
+        assert_eq!(
+            paths(&doc, "$[?length(@) == 4]"),
+            ["$['marks']", "$['name']"]
+        );
+        assert_eq!(paths(&doc, "$[?count(@.*) == 2]"), ["$['tags']"]);
+        assert_eq!(paths(&doc, "$.tags[?match(@, 'b.')]"), ["$['tags'][1]"]);
+        assert_eq!(paths(&doc, "$[?search(@, 'ac')]"), ["$['name']"]);
+        assert_eq!(paths(&doc, "$[?value(@..x) == null]").len(), 0);
+        assert_eq!(paths(&doc, "$.marks[?@ > $.marks[1]]").len(), 2);
+        assert_eq!(paths(&doc, "$.missing[0]").len(), 0);
+    }
+
+    #[test]
+    fn normalized_path_should_escape_names() {
+        let doc = parse(r#"{"it's": {"a\\b\n": [true]}}"#).unwrap();
+        let nodes = doc.query(r#"$["it's"]['a\\b\n'][0]"#).unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].path.to_string(), r"$['it\'s']['a\\b\n'][0]");
+        assert_eq!(nodes[0].path.to_pointer().to_string(), "/it's/a\\b\n/0");
+    }
+
+    #[test]
+    fn parse_should_reject_invalid_queries() {
+        for query in [
+            "",
+            "store",
+            "$.",
+            "$[1",
+            "$[01]",
+            "$[9007199254740992]",
+            "$[-9223372036854775808]",
+            "$[-9223372036854775808:]",
+            "$[?@.* == 1]",
+            "$[?length(@)]",
+            "$[?match(@, 'a') == true]",
+            "$[?count(1) == 1]",
+            "$[?foo(@)]",
+            "$.a ",
+        ] {
+            assert!(
+                matches!(JsonPath::parse(query), Err(JsonError::Path(_))),
+                "{:?}",
+                query
+            );
+        }
+        let err = JsonPath::parse("$[?@.a == ]").unwrap_err().to_string();
+        assert!(
+            err.starts_with("Invalid JSONPath query: expected"),
+            "{}",
+            err
+        );
+        assert!(err.contains("column 11"), "{}", err);
+    }
+}
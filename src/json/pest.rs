@@ -0,0 +1,750 @@
+use pest::{
+    error::{ErrorVariant, InputLocation},
+    iterators::{Pair, Pairs},
+    Parser, Span,
+};
+
+use indexmap::map::Entry;
+
+use super::{
+    number, Dialect, DuplicateKeys, JsonError, JsonObject, JsonValue, Limit, Number, ParseError,
+    ParseOptions,
+};
+
+#[derive(Debug, pest_derive::Parser)]
+#[grammar = "json/json.pest"]
+pub struct JsonParser;
+
+pub fn parse(input: &str) -> Result<JsonValue, JsonError> {
+    parse_with(input, &ParseOptions::default())
+}
+
+/// Strict documents go through the `json` rule. With any [`Dialect`]
+/// extension enabled the `json5` rule, which accepts all of them, is used
+/// instead and the extensions the document actually uses are checked against
+/// the options afterwards.
+///
+/// Errors point at the furthest rule pest attempted unless the caller has
+/// turned on [`enable_error_detail`].
+pub fn parse_with(input: &str, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+    options.limits.check_input_len(input)?;
+    // the generated parser recurses once per level, so bound it beforehand
+    check_depth(input, options)?;
+    let rule = match options.dialect.is_strict() {
+        true => Rule::json,
+        false => Rule::json5,
+    };
+    let pairs = JsonParser::parse(rule, input)
+        .map_err(|e| detailed_parse_error(input, &e).unwrap_or_else(|| to_parse_error(input, e)))?;
+    if let Some(pair) = pairs
+        .clone()
+        .flatten()
+        .find(|pair| !allowed(pair, &options.dialect))
+    {
+        let message = format!("{} are not enabled", describe_extension(pair.as_rule()));
+        return Err(invalid(pair.as_span(), "json", message));
+    }
+    let pair = content(pairs)
+        .next()
+        .ok_or_else(|| ParseError::new(input, 0).with_message("json has no value"))?;
+    parse_value(pair, options)
+}
+
+/// Turn on pest's process-wide [`pest::set_error_detail`], so errors name the
+/// tokens that were expected at the exact position, as the winnow backend's do.
+///
+/// This is left to the program rather than done by [`parse_with`] because it
+/// applies to every pest parser in the process and makes all of them record
+/// every attempt, including on inputs that parse.
+pub fn enable_error_detail() {
+    pest::set_error_detail(true);
+}
+
+/// Scan for brackets nested deeper than [`ParseLimits::max_depth`](super::ParseLimits::max_depth), skipping
+/// strings and comments. Any non-strict dialect goes through the `json5` rule,
+/// which lexes single-quoted strings and comments whether or not they are
+/// enabled, so they are skipped the same way here. Malformed input is left to
+/// the parser.
+fn check_depth(input: &str, options: &ParseOptions) -> Result<(), JsonError> {
+    let max = options.limits.max_depth;
+    let json5 = !options.dialect.is_strict();
+    let bytes = input.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max {
+                    return Err(exceeded(Limit::Depth, max, i));
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            quote @ (b'"' | b'\'') if quote == b'"' || json5 => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'/' if json5 => match bytes.get(i + 1) {
+                Some(b'/') => i += input[i..].find('\n').unwrap_or(input.len() - i),
+                Some(b'*') => i += input[i + 2..].find("*/").map_or(input.len() - i, |n| n + 3),
+                _ => {}
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Whether the token is valid JSON, or an extension `dialect` allows.
+fn allowed(pair: &Pair<Rule>, dialect: &Dialect) -> bool {
+    match pair.as_rule() {
+        Rule::comment => dialect.comments,
+        Rule::trailing_comma => dialect.trailing_commas,
+        Rule::sq_chars => dialect.single_quotes,
+        Rule::identifier => dialect.unquoted_keys,
+        Rule::hex => dialect.hex_numbers,
+        Rule::non_finite => dialect.non_finite_numbers,
+        Rule::plus => dialect.plus_signs,
+        _ => true,
+    }
+}
+
+fn describe_extension(rule: Rule) -> &'static str {
+    match rule {
+        Rule::comment => "comments",
+        Rule::trailing_comma => "trailing commas",
+        Rule::sq_chars => "single-quoted strings",
+        Rule::identifier => "unquoted keys",
+        Rule::hex => "hexadecimal numbers",
+        Rule::plus => "explicit plus signs",
+        _ => "Infinity and NaN",
+    }
+}
+
+/// The pairs without comments and trailing commas, which carry no value.
+fn content<'a>(pairs: Pairs<'a, Rule>) -> impl Iterator<Item = Pair<'a, Rule>> {
+    pairs.filter(|pair| !matches!(pair.as_rule(), Rule::comment | Rule::trailing_comma))
+}
+
+/// Convert a pest error into the [`ParseError`] shared with the winnow backend.
+pub(crate) fn to_parse_error(input: &str, e: pest::error::Error<Rule>) -> ParseError {
+    let offset = match e.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    };
+    let err = ParseError::new(input, offset);
+    match e.variant {
+        ErrorVariant::ParsingError { positives, .. } => {
+            err.with_expected(positives.into_iter().map(describe_rule))
+        }
+        ErrorVariant::CustomError { message } => err.with_message(message),
+    }
+}
+
+/// Pest's error only names the furthest *rule* it attempted, which for
+/// `[1 2]` is the number at offset 1. With [`enable_error_detail`] it also
+/// records the furthest literals, here `,` or `]` at offset 3, which locate the
+/// mistake precisely.
+fn detailed_parse_error(input: &str, e: &pest::error::Error<Rule>) -> Option<ParseError> {
+    let attempts = e.parse_attempts()?;
+    let expected: Vec<_> = attempts
+        .expected_tokens()
+        .into_iter()
+        .map(|token| token.to_string())
+        // implicit WHITESPACE is allowed almost everywhere and only adds noise
+        .filter(|token| !token.trim().is_empty())
+        .map(|token| format!("`{}`", token))
+        .collect();
+    if expected.is_empty() {
+        return None;
+    }
+    Some(ParseError::new(input, attempts.max_position).with_expected(expected))
+}
+
+fn describe_rule(rule: Rule) -> &'static str {
+    match rule {
+        Rule::json | Rule::json5 => "value",
+        Rule::EOI => "end of input",
+        Rule::object | Rule::object5 => "object",
+        Rule::pair | Rule::pair5 => "string key",
+        Rule::array | Rule::array5 => "array",
+        Rule::value => "value",
+        Rule::string
+        | Rule::chars
+        | Rule::unescaped
+        | Rule::sq_string
+        | Rule::sq_chars
+        | Rule::sq_unescaped => "string",
+        Rule::escaped => "escape sequence",
+        Rule::number
+        | Rule::int
+        | Rule::frac
+        | Rule::exp
+        | Rule::hex
+        | Rule::non_finite
+        | Rule::signed
+        | Rule::plus => "number",
+        Rule::comment => "comment",
+        Rule::identifier => "key",
+        Rule::bool => "`true` or `false`",
+        Rule::null => "`null`",
+        _ => "token",
+    }
+}
+
+/// An error for a token the grammar accepted but that is still not valid JSON.
+fn invalid(span: Span, label: &str, message: impl Into<String>) -> JsonError {
+    ParseError::new(span.get_input(), span.start())
+        .with_label(label)
+        .with_message(message)
+        .into()
+}
+
+fn parse_value(pair: Pair<Rule>, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+    match pair.as_rule() {
+        Rule::null => Ok(JsonValue::Null),
+        Rule::bool => Ok(JsonValue::Bool(pair.as_str() == "true")),
+        Rule::number => Ok(parse_number(&pair, options)),
+        Rule::hex => parse_hex(&pair, options),
+        Rule::non_finite => Ok(JsonValue::Double(match pair.as_str() {
+            "Infinity" => f64::INFINITY,
+            "-Infinity" => f64::NEG_INFINITY,
+            _ => f64::NAN,
+        })),
+        // the number after the `+`
+        Rule::signed => match pair.into_inner().nth(1) {
+            Some(inner) => parse_value(inner, options),
+            None => unreachable!(),
+        },
+        Rule::string | Rule::sq_string => Ok(JsonValue::String(parse_string(pair, options)?)),
+        Rule::array | Rule::array5 => Ok(JsonValue::Array(parse_array(pair, options)?)),
+        Rule::object | Rule::object5 => Ok(JsonValue::Object(parse_object(pair, options)?)),
+        Rule::value => {
+            let span = pair.as_span();
+            let inner = pair
+                .into_inner()
+                .next()
+                .ok_or_else(|| invalid(span, "value", "expected value, found none"))?;
+            parse_value(inner, options)
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn parse_number(pair: &Pair<Rule>, options: &ParseOptions) -> JsonValue {
+    // the grammar guarantees a valid JSON number, so only pick the representation
+    match options.lossless_numbers {
+        true => JsonValue::Number(Number::from_lexeme(pair.as_str())),
+        false => number::native(pair.as_str(), JsonValue::Integer, JsonValue::Double),
+    }
+}
+
+fn parse_hex(pair: &Pair<Rule>, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+    let s = pair.as_str();
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(s) => ("-", s),
+        None => ("", s),
+    };
+    // skip the `0x`
+    let v = u128::from_str_radix(&s[2..], 16)
+        .map_err(|e| invalid(pair.as_span(), "number", e.to_string()))?;
+    let lexeme = format!("{}{}", sign, v);
+    Ok(match options.lossless_numbers {
+        true => JsonValue::Number(Number::from_lexeme(&lexeme)),
+        false => number::native(&lexeme, JsonValue::Integer, JsonValue::Double),
+    })
+}
+
+/// Decode a quoted `string` or `sq_string` token.
+fn parse_string(pair: Pair<Rule>, options: &ParseOptions) -> Result<String, JsonError> {
+    let offset = pair.as_span().start();
+    let chars = pair
+        .into_inner()
+        .next()
+        .expect("a string token holds its chars");
+    let s =
+        unescape(chars.as_str()).map_err(|message| invalid(chars.as_span(), "string", message))?;
+    check_string_len(&s, offset, options)?;
+    Ok(s)
+}
+
+fn check_string_len(s: &str, offset: usize, options: &ParseOptions) -> Result<(), JsonError> {
+    let max = options.limits.max_string_len;
+    match s.len() > max {
+        true => Err(exceeded(Limit::StringLength, max, offset)),
+        false => Ok(()),
+    }
+}
+
+fn exceeded(limit: Limit, max: usize, offset: usize) -> JsonError {
+    JsonError::LimitExceeded { limit, max, offset }
+}
+
+/// Decode the escape sequences of a `chars` token. The grammar already checked
+/// every escape is well formed, so only surrogate pairing is left to verify.
+pub(crate) fn unescape(s: &str) -> Result<String, String> {
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            ret.push(c);
+            continue;
+        }
+        let c = match chars.next() {
+            Some('b') => '\u{08}',
+            Some('f') => '\u{0c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => unescape_unicode(&mut chars)?,
+            Some(c) => c,
+            None => return Err("unterminated escape".to_string()),
+        };
+        ret.push(c);
+    }
+    Ok(ret)
+}
+
+fn unescape_unicode(chars: &mut std::str::Chars) -> Result<char, String> {
+    let high = hex4(chars)?;
+    let code = match high {
+        0xD800..=0xDBFF => {
+            let low = match (chars.next(), chars.next()) {
+                (Some('\\'), Some('u')) => hex4(chars)?,
+                _ => return Err(lone_surrogate(high)),
+            };
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(lone_surrogate(high));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        }
+        0xDC00..=0xDFFF => return Err(lone_surrogate(high)),
+        _ => high,
+    };
+    char::from_u32(code).ok_or_else(|| lone_surrogate(code))
+}
+
+fn hex4(chars: &mut std::str::Chars) -> Result<u32, String> {
+    let s: String = chars.take(4).collect();
+    u32::from_str_radix(&s, 16).map_err(|_| format!("invalid unicode escape \\u{}", s))
+}
+
+fn lone_surrogate(code: u32) -> String {
+    format!("lone surrogate \\u{:04x}", code)
+}
+
+fn parse_array(pair: Pair<Rule>, options: &ParseOptions) -> Result<Vec<JsonValue>, JsonError> {
+    let max = options.limits.max_array_len;
+    content(pair.into_inner())
+        .enumerate()
+        .map(|(i, pair)| match i < max {
+            true => parse_value(pair, options),
+            false => Err(exceeded(Limit::ArrayLength, max, pair.as_span().start())),
+        })
+        .collect()
+}
+
+fn parse_object(pair: Pair<Rule>, options: &ParseOptions) -> Result<JsonObject, JsonError> {
+    let max = options.limits.max_object_len;
+    let mut obj = JsonObject::new();
+    for (i, pair) in content(pair.into_inner()).enumerate() {
+        let span = pair.as_span();
+        if i >= max {
+            return Err(exceeded(Limit::ObjectLength, max, span.start()));
+        }
+        let mut inner = content(pair.into_inner());
+        let (Some(key), Some(value)) = (inner.next(), inner.next()) else {
+            return Err(invalid(
+                span,
+                "object",
+                "expected key and value, found none",
+            ));
+        };
+        let key = match key.as_rule() {
+            Rule::identifier => {
+                check_string_len(key.as_str(), key.as_span().start(), options)?;
+                key.as_str().to_string()
+            }
+            _ => parse_string(key, options)?,
+        };
+        let value = parse_value(value, options)?;
+        match obj.entry(key) {
+            Entry::Vacant(e) => {
+                e.insert(value);
+            }
+            Entry::Occupied(mut e) => match options.duplicate_keys {
+                DuplicateKeys::LastWins => {
+                    e.insert(value);
+                }
+                DuplicateKeys::FirstWins => {}
+                DuplicateKeys::Error => {
+                    return Err(JsonError::DuplicateKey {
+                        key: e.key().clone(),
+                        offset: span.start(),
+                    })
+                }
+            },
+        }
+    }
+    Ok(obj)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pest::consumes_to;
+    use pest::parses_to;
+
+    use super::*;
+
+    #[test]
+    fn pest_parse_null_should_work() -> Result<()> {
+        let input = "null";
+        let parsed = JsonParser::parse(Rule::null, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(result, JsonValue::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn pest_parse_bool_should_work() -> Result<()> {
+        let input = "false";
+        let parsed = JsonParser::parse(Rule::bool, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(result, JsonValue::Bool(false));
+
+        let input = "true";
+        let parsed = JsonParser::parse(Rule::bool, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(result, JsonValue::Bool(true));
+        Ok(())
+    }
+
+    #[test]
+    fn pest_parse_number_should_work() -> Result<()> {
+        let input = "1.23";
+        let parsed = JsonParser::parse(Rule::number, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(result, JsonValue::Double(1.23));
+
+        let input = "-1.23";
+        let parsed = JsonParser::parse(Rule::number, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(result, JsonValue::Double(-1.23));
+
+        let input = "-12";
+        let parsed = JsonParser::parse(Rule::number, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(result, JsonValue::Integer(-12));
+        Ok(())
+    }
+
+    #[test]
+    fn pest_parse_string_should_work() -> Result<()> {
+        let input = r#""hello \"world\"""#;
+        let parsed = JsonParser::parse(Rule::string, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(result, JsonValue::String("hello \"world\"".to_string()));
+
+        let input = r#""\\\/\b\f\n\r\t\u00e9\u4E2D \ud83d\ude00""#;
+        let parsed = JsonParser::parse(Rule::string, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(
+            result,
+            JsonValue::String("\\/\u{08}\u{0c}\n\r\té中 😀".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pest_parse_invalid_string_should_fail() {
+        for input in [
+            r#""\ud83d""#,
+            r#""\ud83dabc""#,
+            r#""\ude00""#,
+            r#""\u12""#,
+            r#""\x""#,
+            "\"tab\there\"",
+        ] {
+            assert!(parse(&format!("[{}]", input)).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn pest_and_winnow_should_decode_strings_identically() {
+        let input = r#"{"k\u00e9y": ["say \"hi\"", "\ud83d\ude00\n", "a\/b"]}"#;
+        assert_eq!(
+            parse(input).unwrap(),
+            crate::json::winnow::parse(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn pest_parse_array_should_work() -> Result<()> {
+        let input = r#"[1,2,3]"#;
+        let parsed = JsonParser::parse(Rule::array, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        assert_eq!(
+            result,
+            JsonValue::Array(vec![
+                JsonValue::Integer(1),
+                JsonValue::Integer(2),
+                JsonValue::Integer(3),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pest_parse_object_should_work() -> Result<()> {
+        let input = r#"{"a": 123}"#;
+        let parsed = JsonParser::parse(Rule::object, input)?.next().unwrap();
+        let result = parse_value(parsed, &ParseOptions::default())?;
+        let mut expect = JsonObject::new();
+        expect.insert("a".to_string(), JsonValue::Integer(123));
+        assert_eq!(result, JsonValue::Object(expect));
+        Ok(())
+    }
+
+    #[test]
+    fn pest_parse_duplicate_keys_should_follow_policy() {
+        let input = r#"{"b": 1, "a": 2, "b": 3}"#;
+        let JsonValue::Object(obj) = parse(input).unwrap() else {
+            panic!("expected object");
+        };
+        assert_eq!(obj.keys().collect::<Vec<_>>(), ["b", "a"]);
+        assert_eq!(obj.get("b"), Some(&JsonValue::Integer(3)));
+
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::FirstWins,
+            ..Default::default()
+        };
+        let JsonValue::Object(obj) = parse_with(input, &options).unwrap() else {
+            panic!("expected object");
+        };
+        assert_eq!(obj.get("b"), Some(&JsonValue::Integer(1)));
+
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..Default::default()
+        };
+        match parse_with(input, &options) {
+            Err(JsonError::DuplicateKey { key, offset }) => {
+                assert_eq!(key, "b");
+                assert_eq!(offset, 17);
+            }
+            ret => panic!("expected duplicate key error, got {:?}", ret),
+        }
+    }
+
+    #[test]
+    fn pest_parse_rule_should_work() -> Result<()> {
+        parses_to! {
+            parser: JsonParser,
+            input: r#"{"hello":"world"}"#,
+            rule: Rule::json,
+            tokens: [
+                value(0, 17, [
+                    object(0, 17, [
+                        pair(1, 16, [
+                            string(1, 8, [chars(2, 7)]),
+                            value(9, 16, [
+                                string(9, 16, [chars(10, 15)]),
+                            ])
+                        ])
+                    ])
+                ]),
+                EOI(17, 17)
+            ]
+        };
+        Ok(())
+    }
+
+    #[test]
+    fn pest_parse_json_should_work() {
+        let input = r#"{"name": "John Doe", "marks": [90.0, -80.1, 85.2]}"#;
+        let ret = parse(input).unwrap();
+        let JsonValue::Object(obj) = ret else {
+            panic!("expected object");
+        };
+        assert_eq!(obj.get("name"), Some(&JsonValue::String("John Doe".into())));
+        assert!(parse("{").is_err());
+    }
+
+    #[test]
+    fn pest_parse_error_should_report_position() {
+        enable_error_detail();
+        let err = |input| match parse(input) {
+            Err(JsonError::Parse(e)) => e,
+            ret => panic!("expected parse error, got {:?}", ret),
+        };
+
+        let e = err("{\n  \"a\": [1 2]\n}");
+        assert_eq!((e.offset, e.line, e.column), (12, 2, 11));
+        assert_eq!(e.expected, ["`,`", "`]`"]);
+
+        let e = err(r#"["\ud800"]"#);
+        assert_eq!((e.column, e.label.as_deref()), (3, Some("string")));
+        assert_eq!(e.message.as_deref(), Some("lone surrogate \\ud800"));
+
+        // a raw newline must not be skipped as implicit whitespace
+        let e = err("[\"a\n\"]");
+        assert_eq!((e.line, e.column), (1, 4));
+
+        let e = err("{\"a\": 1} x");
+        assert_eq!(e.offset, 9);
+        assert_eq!(e.expected, ["end of input"]);
+    }
+
+    #[test]
+    fn pest_parse_document_should_accept_any_value() {
+        for input in [
+            "[]",
+            " { } ",
+            "\"x\"",
+            "\n42\r\n",
+            "null",
+            "[{}, [], {\"a\": {}}]",
+        ] {
+            assert_eq!(
+                parse(input).unwrap(),
+                crate::json::winnow::parse(input).unwrap(),
+                "{:?}",
+                input
+            );
+        }
+        for input in ["", "[] []", "1 2", "{},", "nullx", "{,}", "[\"\tx\"]"] {
+            assert!(parse(input).is_err(), "{:?}", input);
+        }
+        // a leading space belongs to the string, not to implicit whitespace
+        assert_eq!(parse("\" a\"").unwrap(), JsonValue::String(" a".into()));
+    }
+
+    #[test]
+    fn pest_parse_dialect_should_match_winnow() {
+        let input = "{a: [0x10, 'x\\'y', +1, +0x1], /* c */ 'b': -Infinity, c: +Infinity,}";
+        let options = ParseOptions {
+            dialect: Dialect::JSON5,
+            ..Default::default()
+        };
+        let JsonValue::Object(obj) = parse_with(input, &options).unwrap() else {
+            panic!("expected object");
+        };
+        assert_eq!(
+            obj["a"],
+            crate::json::parse(r#"[16, "x'y", 1, 1]"#).unwrap()
+        );
+        assert_eq!(obj["b"], JsonValue::Double(f64::NEG_INFINITY));
+        assert_eq!(obj["c"], JsonValue::Double(f64::INFINITY));
+        assert_eq!(
+            JsonValue::Object(obj),
+            crate::json::winnow::parse_with(input, &options).unwrap()
+        );
+
+        let options = ParseOptions {
+            dialect: Dialect {
+                comments: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        match parse_with("[1, // x\n 2,]", &options) {
+            Err(JsonError::Parse(e)) => {
+                assert_eq!((e.line, e.column), (2, 3));
+                assert_eq!(
+                    e.message.as_deref(),
+                    Some("trailing commas are not enabled")
+                );
+            }
+            ret => panic!("expected parse error, got {:?}", ret),
+        }
+        match parse_with("[+1]", &options) {
+            Err(JsonError::Parse(e)) => {
+                assert_eq!(e.column, 2);
+                assert_eq!(
+                    e.message.as_deref(),
+                    Some("explicit plus signs are not enabled")
+                );
+            }
+            ret => panic!("expected parse error, got {:?}", ret),
+        }
+    }
+
+    #[test]
+    fn pest_parse_limits_should_match_winnow() {
+        let deep = "[".repeat(100_000);
+        assert!(matches!(
+            parse(&deep),
+            Err(JsonError::LimitExceeded {
+                limit: Limit::Depth,
+                offset: 64,
+                ..
+            })
+        ));
+
+        // the json5 rule lexes these whatever the dialect enables, so the
+        // brackets inside them must not hide the nesting around them
+        let quoted = format!("{}1{}", "[']',".repeat(100_000), "]".repeat(100_000));
+        let commented = "[/*]*/".repeat(100_000);
+        let trailing_commas = Dialect {
+            trailing_commas: true,
+            ..Dialect::STRICT
+        };
+        for dialect in [Dialect::JSONC, trailing_commas] {
+            let options = ParseOptions {
+                dialect,
+                ..Default::default()
+            };
+            for input in [&quoted, &commented] {
+                assert!(
+                    matches!(
+                        parse_with(input, &options),
+                        Err(JsonError::LimitExceeded {
+                            limit: Limit::Depth,
+                            ..
+                        })
+                    ),
+                    "{:?}",
+                    dialect
+                );
+            }
+        }
+
+        let limits = super::super::ParseLimits {
+            max_depth: 2,
+            max_string_len: 3,
+            max_array_len: 2,
+            max_object_len: 1,
+            max_input_len: 32,
+        };
+        let options = ParseOptions {
+            dialect: Dialect::JSON5,
+            limits,
+            ..Default::default()
+        };
+        for input in [
+            "[{\"a\": [1]}]",
+            "[\"[[\", '[[[', /* [[[ */ [1]]",
+            "[\"abc\", \"\\u00e9\\u00e9\"]",
+            "{abcd: 1}",
+            "{'abcd': 1}",
+            "[1, 2, 3]",
+            "[1, 2,]",
+            "{\"a\": 1, \"b\": 2}",
+            "{\"a\": 1,}",
+            "[                                ]",
+        ] {
+            let expected = crate::json::winnow::parse_with(input, &options);
+            match (parse_with(input, &options), expected) {
+                (Ok(v), Ok(expected)) => assert_eq!(v, expected, "{}", input),
+                (Err(e), Err(expected)) => {
+                    assert_eq!(e.to_string(), expected.to_string(), "{}", input)
+                }
+                (ret, expected) => panic!("{}: {:?} != {:?}", input, ret, expected),
+            }
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use std::{fmt, mem, str::FromStr};
+
+use thiserror::Error;
+
+use super::JsonValue;
+
+/// A parsed JSON Pointer (RFC 6901), e.g. `/address/zip` or `/marks/0`.
+///
+/// Tokens are stored unescaped; `Display` escapes `~` as `~0` and `/` as
+/// `~1` again. The empty pointer refers to the whole document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PointerError {
+    #[error("Invalid JSON pointer {0:?}: must be empty or start with `/`")]
+    MissingSlash(String),
+    #[error("Invalid JSON pointer {0:?}: `~` must be followed by `0` or `1`")]
+    InvalidEscape(String),
+    #[error("No member {key:?} in the object at {at:?}")]
+    MissingKey { at: String, key: String },
+    #[error("Invalid array index {token:?} for the array at {at:?}")]
+    InvalidIndex { at: String, token: String },
+    #[error("Index {token} is out of range for the array of length {len} at {at:?}")]
+    IndexOutOfRange {
+        at: String,
+        token: String,
+        len: usize,
+    },
+    #[error("Cannot look up {token:?} in the {kind} at {at:?}")]
+    NotAContainer {
+        at: String,
+        token: String,
+        kind: &'static str,
+    },
+    #[error("Cannot remove the whole document")]
+    RemoveRoot,
+}
+
+impl JsonPointer {
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(s: &str) -> Result<Self, PointerError> {
+        if s.is_empty() {
+            return Ok(Self::root());
+        }
+        let Some(rest) = s.strip_prefix('/') else {
+            return Err(PointerError::MissingSlash(s.to_string()));
+        };
+        let tokens = rest
+            .split('/')
+            .map(|token| unescape(token).ok_or_else(|| PointerError::InvalidEscape(s.to_string())))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { tokens })
+    }
+
+    /// The unescaped reference tokens.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    /// A new pointer one level below this one.
+    pub fn join(&self, token: impl Into<String>) -> Self {
+        let mut pointer = self.clone();
+        pointer.push(token);
+        pointer
+    }
+
+    /// The pointer to the containing value and the last token, `None` for the root.
+    pub fn split_last(&self) -> Option<(JsonPointer, &str)> {
+        let (last, parent) = self.tokens.split_last()?;
+        let parent = JsonPointer {
+            tokens: parent.to_vec(),
+        };
+        Some((parent, last))
+    }
+
+    pub fn get<'v>(&self, value: &'v JsonValue) -> Result<&'v JsonValue, PointerError> {
+        let mut node = value;
+        for depth in 0..self.tokens.len() {
+            node = match node {
+                JsonValue::Object(obj) => obj
+                    .get(&self.tokens[depth])
+                    .ok_or_else(|| self.missing_key(depth))?,
+                JsonValue::Array(values) => &values[self.index(depth, values.len())?],
+                v => return Err(self.not_a_container(depth, v)),
+            };
+        }
+        Ok(node)
+    }
+
+    pub fn get_mut<'v>(&self, value: &'v mut JsonValue) -> Result<&'v mut JsonValue, PointerError> {
+        let mut node = value;
+        for depth in 0..self.tokens.len() {
+            node = match node {
+                JsonValue::Object(obj) => obj
+                    .get_mut(&self.tokens[depth])
+                    .ok_or_else(|| self.missing_key(depth))?,
+                JsonValue::Array(values) => {
+                    let index = self.index(depth, values.len())?;
+                    &mut values[index]
+                }
+                v => return Err(self.not_a_container(depth, v)),
+            };
+        }
+        Ok(node)
+    }
+
+    /// Add `new` at this pointer with the semantics of a JSON Patch `add`: an
+    /// object member is inserted or replaced, an array element is inserted
+    /// before the given index (`-` appends), and the root is replaced.
+    /// Returns the value that was replaced, if any.
+    pub fn insert(
+        &self,
+        value: &mut JsonValue,
+        new: JsonValue,
+    ) -> Result<Option<JsonValue>, PointerError> {
+        let Some((parent, last)) = self.split_last() else {
+            return Ok(Some(mem::replace(value, new)));
+        };
+        let depth = parent.tokens.len();
+        match parent.get_mut(value)? {
+            JsonValue::Object(obj) => Ok(obj.insert(last.to_string(), new)),
+            JsonValue::Array(values) => {
+                let index = if last == "-" {
+                    values.len()
+                } else {
+                    self.index(depth, values.len() + 1)?
+                };
+                values.insert(index, new);
+                Ok(None)
+            }
+            v => Err(self.not_a_container(depth, v)),
+        }
+    }
+
+    /// Remove and return the value at this pointer. Object members after it
+    /// keep their order, array elements after it shift down.
+    pub fn remove(&self, value: &mut JsonValue) -> Result<JsonValue, PointerError> {
+        let Some((parent, last)) = self.split_last() else {
+            return Err(PointerError::RemoveRoot);
+        };
+        let depth = parent.tokens.len();
+        match parent.get_mut(value)? {
+            JsonValue::Object(obj) => obj
+                .shift_remove(last)
+                .ok_or_else(|| self.missing_key(depth)),
+            JsonValue::Array(values) => {
+                let index = self.index(depth, values.len())?;
+                Ok(values.remove(index))
+            }
+            v => Err(self.not_a_container(depth, v)),
+        }
+    }
+
+    /// The token at `depth` as an array index below `bound`. Leading zeros
+    /// are not allowed; `-` names the element after the last one.
+    fn index(&self, depth: usize, bound: usize) -> Result<usize, PointerError> {
+        let token = &self.tokens[depth];
+        let at = self.prefix(depth);
+        if token == "-" {
+            return Err(PointerError::IndexOutOfRange {
+                at,
+                token: token.clone(),
+                len: bound,
+            });
+        }
+        let valid = token == "0" || (!token.starts_with('0') && !token.is_empty());
+        let index = token
+            .parse::<usize>()
+            .ok()
+            .filter(|_| valid && token.bytes().all(|b| b.is_ascii_digit()))
+            .ok_or_else(|| PointerError::InvalidIndex {
+                at: at.clone(),
+                token: token.clone(),
+            })?;
+        if index >= bound {
+            return Err(PointerError::IndexOutOfRange {
+                at,
+                token: token.clone(),
+                len: bound,
+            });
+        }
+        Ok(index)
+    }
+
+    /// The first `depth` tokens, as a pointer string.
+    fn prefix(&self, depth: usize) -> String {
+        JsonPointer {
+            tokens: self.tokens[..depth].to_vec(),
+        }
+        .to_string()
+    }
+
+    fn missing_key(&self, depth: usize) -> PointerError {
+        PointerError::MissingKey {
+            at: self.prefix(depth),
+            key: self.tokens[depth].clone(),
+        }
+    }
+
+    fn not_a_container(&self, depth: usize, value: &JsonValue) -> PointerError {
+        PointerError::NotAContainer {
+            at: self.prefix(depth),
+            token: self.tokens[depth].clone(),
+            kind: value.kind(),
+        }
+    }
+}
+
+fn unescape(token: &str) -> Option<String> {
+    if !token.contains('~') {
+        return Some(token.to_string());
+    }
+    let mut s = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next()? {
+                '0' => s.push('~'),
+                '1' => s.push('/'),
+                _ => return None,
+            },
+            c => s.push(c),
+        }
+    }
+    Some(s)
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", token.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = PointerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl JsonValue {
+    /// The value at a JSON Pointer such as `/address/zip`.
+    pub fn pointer(&self, pointer: &str) -> Result<&JsonValue, PointerError> {
+        JsonPointer::parse(pointer)?.get(self)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut JsonValue, PointerError> {
+        JsonPointer::parse(pointer)?.get_mut(self)
+    }
+
+    /// See [`JsonPointer::insert`].
+    pub fn pointer_insert(
+        &mut self,
+        pointer: &str,
+        value: JsonValue,
+    ) -> Result<Option<JsonValue>, PointerError> {
+        JsonPointer::parse(pointer)?.insert(self, value)
+    }
+
+    /// See [`JsonPointer::remove`].
+    pub fn pointer_remove(&mut self, pointer: &str) -> Result<JsonValue, PointerError> {
+        JsonPointer::parse(pointer)?.remove(self)
+    }
+
+    /// The JSON type name, e.g. `object` or `string`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Integer(_) | JsonValue::Double(_) | JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse;
+
+    // the example document from RFC 6901, section 5
+    const RFC_DOC: &str = r#"{
+        "foo": ["bar", "baz"],
+        "": 0,
+        "a/b": 1,
+        "c%d": 2,
+        "e^f": 3,
+        "g|h": 4,
+        "i\\j": 5,
+        "k\"l": 6,
+        " ": 7,
+        "m~n": 8
+    }"#;
+
+    #[test]
+    fn pointer_should_follow_rfc_examples() {
+        let doc = parse(RFC_DOC).unwrap();
+        assert_eq!(doc.pointer("").unwrap(), &doc);
+        assert_eq!(
+            doc.pointer("/foo").unwrap(),
+            &parse(r#"["bar", "baz"]"#).unwrap()
+        );
+        for (pointer, expected) in [
+            ("/foo/0", JsonValue::String("bar".into())),
+            ("/", JsonValue::Integer(0)),
+            ("/a~1b", JsonValue::Integer(1)),
+            ("/c%d", JsonValue::Integer(2)),
+            ("/e^f", JsonValue::Integer(3)),
+            ("/g|h", JsonValue::Integer(4)),
+            ("/i\\j", JsonValue::Integer(5)),
+            ("/k\"l", JsonValue::Integer(6)),
+            ("/ ", JsonValue::Integer(7)),
+            ("/m~0n", JsonValue::Integer(8)),
+        ] {
+            assert_eq!(doc.pointer(pointer).unwrap(), &expected, "{}", pointer);
+            assert_eq!(JsonPointer::parse(pointer).unwrap().to_string(), pointer);
+        }
+    }
+
+    #[test]
+    fn pointer_should_report_errors() {
+        let doc = parse(RFC_DOC).unwrap();
+        let err = |pointer: &str| doc.pointer(pointer).unwrap_err();
+        assert_eq!(err("foo"), PointerError::MissingSlash("foo".into()));
+        assert_eq!(err("/m~2n"), PointerError::InvalidEscape("/m~2n".into()));
+        assert_eq!(
+            err("/missing"),
+            PointerError::MissingKey {
+                at: "".into(),
+                key: "missing".into()
+            }
+        );
+        assert_eq!(
+            err("/foo/2"),
+            PointerError::IndexOutOfRange {
+                at: "/foo".into(),
+                token: "2".into(),
+                len: 2
+            }
+        );
+        for token in ["01", "-1", "x", "", "+1"] {
+            let pointer = format!("/foo/{}", token);
+            assert!(
+                matches!(err(&pointer), PointerError::InvalidIndex { .. }),
+                "{}",
+                pointer
+            );
+        }
+        assert!(matches!(
+            err("/foo/-"),
+            PointerError::IndexOutOfRange { .. }
+        ));
+        assert_eq!(
+            err("/foo/0/x").to_string(),
+            r#"Cannot look up "x" in the string at "/foo/0""#
+        );
+    }
+
+    #[test]
+    fn pointer_mut_should_work() {
+        let mut doc = parse(r#"{"address": {"zip": 10001}}"#).unwrap();
+        *doc.pointer_mut("/address/zip").unwrap() = JsonValue::Integer(10002);
+        assert_eq!(doc.to_string(), r#"{"address":{"zip":10002}}"#);
+    }
+
+    #[test]
+    fn pointer_insert_and_remove_should_work() {
+        let mut doc = parse(r#"{"a": 1, "list": [1, 2], "z": 3}"#).unwrap();
+        assert_eq!(
+            doc.pointer_insert("/a", JsonValue::Null).unwrap(),
+            Some(JsonValue::Integer(1))
+        );
+        doc.pointer_insert("/b", JsonValue::Bool(true)).unwrap();
+        doc.pointer_insert("/list/0", JsonValue::Integer(0))
+            .unwrap();
+        doc.pointer_insert("/list/-", JsonValue::Integer(3))
+            .unwrap();
+        doc.pointer_insert("/list/4", JsonValue::Integer(4))
+            .unwrap();
+        assert!(doc.pointer_insert("/list/6", JsonValue::Null).is_err());
+        assert!(doc.pointer_insert("/missing/x", JsonValue::Null).is_err());
+        assert_eq!(
+            doc.to_string(),
+            r#"{"a":null,"list":[0,1,2,3,4],"z":3,"b":true}"#
+        );
+
+        assert_eq!(
+            doc.pointer_remove("/list/1").unwrap(),
+            JsonValue::Integer(1)
+        );
+        assert_eq!(doc.pointer_remove("/a").unwrap(), JsonValue::Null);
+        assert!(doc.pointer_remove("/a").is_err());
+        assert_eq!(doc.pointer_remove(""), Err(PointerError::RemoveRoot));
+        assert_eq!(doc.to_string(), r#"{"list":[0,2,3,4],"z":3,"b":true}"#);
+
+        doc.pointer_insert("", JsonValue::Null).unwrap();
+        assert_eq!(doc, JsonValue::Null);
+    }
+}
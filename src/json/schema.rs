@@ -0,0 +1,655 @@
+//! Validation against a subset of JSON Schema 2020-12.
+//!
+//! Supported keywords: `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `prefixItems`, `items`, `minimum`, `maximum`,
+//! `exclusiveMinimum`, `exclusiveMaximum`, `minLength`, `maxLength`,
+//! `pattern`, `minItems`, `maxItems`, `minProperties`, `maxProperties`,
+//! `allOf`, `anyOf`, `oneOf`, `not` and `$ref` to `#...` within the same
+//! document. Other keywords are ignored.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use regex::Regex;
+use thiserror::Error;
+
+use super::{path::values_equal, JsonPointer, JsonValue, PointerError};
+
+/// `$ref` chains deeper than this are reported instead of followed, so
+/// `{"$ref": "#"}` cannot recurse forever.
+const MAX_DEPTH: usize = 128;
+
+/// A schema checked for unresolvable `$ref`s and invalid patterns, ready to
+/// validate any number of instances.
+///
+/// ```
+/// use grammar::json::{self, Schema};
+///
+/// let schema = Schema::new(json::parse(r#"{
+///     "type": "object",
+///     "required": ["name"],
+///     "properties": {"age": {"type": "integer", "minimum": 0}}
+/// }"#)?)?;
+/// let errors = schema.validate(&json::parse(r#"{"age": -1}"#)?).unwrap_err();
+/// assert_eq!(errors[0].to_string(), r#"at "": missing required property "name" (schema "/required")"#);
+/// assert_eq!(errors[1].instance_path.to_string(), "/age");
+/// assert_eq!(errors[1].schema_path.to_string(), "/properties/age/minimum");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: JsonValue,
+    patterns: HashMap<String, Regex>,
+}
+
+/// One way an instance violates the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Where in the instance, e.g. `/address/zip`.
+    pub instance_path: JsonPointer,
+    /// The keyword that failed, e.g. `/properties/address/$ref/type`.
+    pub schema_path: JsonPointer,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SchemaError {
+    #[error("Invalid schema at {path:?}: {message}")]
+    Invalid { path: String, message: String },
+    #[error("Cannot resolve $ref {reference:?} at {path:?}: {source}")]
+    UnresolvedRef {
+        path: String,
+        reference: String,
+        source: PointerError,
+    },
+}
+
+impl Schema {
+    pub fn new(root: JsonValue) -> Result<Self, SchemaError> {
+        let mut schema = Self {
+            root,
+            patterns: HashMap::new(),
+        };
+        let mut patterns = HashMap::new();
+        // `$ref` targets are compiled once each, from a queue rather than
+        // recursively, so reference cycles and long chains both end
+        let mut compiled = HashSet::new();
+        let mut pending = vec![JsonPointer::root()];
+        while let Some(path) = pending.pop() {
+            if compiled.insert(path.clone()) {
+                let target = path.get(&schema.root).expect("resolved by `compile`");
+                schema.compile(target, &path, &mut patterns, &mut pending)?;
+            }
+        }
+        schema.patterns = patterns;
+        Ok(schema)
+    }
+
+    /// Every violation, in schema order, or `Ok` if the instance is valid.
+    pub fn validate(&self, instance: &JsonValue) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let root = JsonPointer::root();
+        self.check(&self.root, &root, instance, &root, 0, &mut errors);
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    pub fn is_valid(&self, instance: &JsonValue) -> bool {
+        self.validate(instance).is_ok()
+    }
+
+    /// Check every subschema reachable through a supported keyword, and queue
+    /// the target of every `$ref` in `pending`.
+    fn compile(
+        &self,
+        schema: &JsonValue,
+        path: &JsonPointer,
+        patterns: &mut HashMap<String, Regex>,
+        pending: &mut Vec<JsonPointer>,
+    ) -> Result<(), SchemaError> {
+        let invalid = |keyword: &str, message: String| SchemaError::Invalid {
+            path: path.join(keyword).to_string(),
+            message,
+        };
+        let obj = match schema {
+            JsonValue::Bool(_) => return Ok(()),
+            JsonValue::Object(obj) => obj,
+            v => {
+                return Err(SchemaError::Invalid {
+                    path: path.to_string(),
+                    message: format!("a schema must be an object or boolean, found {}", v.kind()),
+                })
+            }
+        };
+        for (keyword, value) in obj {
+            let path = path.join(keyword.as_str());
+            match (keyword.as_str(), value) {
+                ("properties" | "$defs", JsonValue::Object(schemas)) => {
+                    for (name, schema) in schemas {
+                        self.compile(schema, &path.join(name.as_str()), patterns, pending)?;
+                    }
+                }
+                ("prefixItems" | "allOf" | "anyOf" | "oneOf", JsonValue::Array(schemas)) => {
+                    for (i, schema) in schemas.iter().enumerate() {
+                        self.compile(schema, &path.join(i.to_string()), patterns, pending)?;
+                    }
+                }
+                ("additionalProperties" | "items" | "not", schema) => {
+                    self.compile(schema, &path, patterns, pending)?
+                }
+                ("pattern", JsonValue::String(pattern)) => {
+                    let re = Regex::new(pattern).map_err(|e| invalid(keyword, e.to_string()))?;
+                    patterns.insert(pattern.clone(), re);
+                }
+                ("$ref", JsonValue::String(reference)) => {
+                    let target = target(reference)
+                        .and_then(|target| target.get(&self.root).map(|_| target))
+                        .map_err(|source| SchemaError::UnresolvedRef {
+                            path: path.to_string(),
+                            reference: reference.clone(),
+                            source,
+                        })?;
+                    pending.push(target);
+                }
+                ("type", JsonValue::String(name)) => {
+                    check_type_name(name).map_err(|e| invalid(keyword, e))?
+                }
+                ("type", JsonValue::Array(names)) => {
+                    for name in names {
+                        let JsonValue::String(name) = name else {
+                            return Err(invalid(keyword, "type names must be strings".to_string()));
+                        };
+                        check_type_name(name).map_err(|e| invalid(keyword, e))?;
+                    }
+                }
+                (
+                    "properties" | "$defs" | "prefixItems" | "allOf" | "anyOf" | "oneOf"
+                    | "pattern" | "$ref" | "type",
+                    v,
+                ) => return Err(invalid(keyword, format!("unexpected {}", v.kind()))),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The subschema a `$ref` such as `#/$defs/address` points to.
+    fn resolve(&self, reference: &str) -> Result<&JsonValue, PointerError> {
+        target(reference)?.get(&self.root)
+    }
+
+    fn check(
+        &self,
+        schema: &JsonValue,
+        schema_path: &JsonPointer,
+        instance: &JsonValue,
+        instance_path: &JsonPointer,
+        depth: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let obj = match schema {
+            JsonValue::Object(obj) => obj,
+            JsonValue::Bool(false) => {
+                return errors.push(ValidationError {
+                    instance_path: instance_path.clone(),
+                    schema_path: schema_path.clone(),
+                    message: "no value is allowed here".to_string(),
+                })
+            }
+            _ => return,
+        };
+        for (keyword, value) in obj {
+            let schema_path = schema_path.join(keyword.as_str());
+            let mut fail = |message: String| {
+                errors.push(ValidationError {
+                    instance_path: instance_path.clone(),
+                    schema_path: schema_path.clone(),
+                    message,
+                })
+            };
+            match (keyword.as_str(), value, instance) {
+                ("type", expected, instance) => {
+                    let matches = match expected {
+                        JsonValue::String(name) => has_type(instance, name),
+                        JsonValue::Array(names) => names
+                            .iter()
+                            .any(|name| matches!(name, JsonValue::String(name) if has_type(instance, name))),
+                        _ => true,
+                    };
+                    if !matches {
+                        fail(format!(
+                            "expected {}, found {}",
+                            expected_types(expected),
+                            instance.kind()
+                        ));
+                    }
+                }
+                ("enum", JsonValue::Array(values), instance)
+                    if !values.iter().any(|v| values_equal(v, instance)) =>
+                {
+                    fail(format!("{} is not one of {}", instance, value));
+                }
+                ("const", expected, instance) if !values_equal(expected, instance) => {
+                    fail(format!("expected {}, found {}", expected, instance));
+                }
+                ("properties", JsonValue::Object(schemas), JsonValue::Object(members)) => {
+                    for (name, schema) in schemas {
+                        if let Some(member) = members.get(name) {
+                            self.check(
+                                schema,
+                                &schema_path.join(name.as_str()),
+                                member,
+                                &instance_path.join(name.as_str()),
+                                depth,
+                                errors,
+                            );
+                        }
+                    }
+                }
+                ("required", JsonValue::Array(names), JsonValue::Object(members)) => {
+                    for name in names {
+                        if let JsonValue::String(name) = name {
+                            if !members.contains_key(name) {
+                                fail(format!("missing required property {:?}", name));
+                            }
+                        }
+                    }
+                }
+                ("additionalProperties", schema, JsonValue::Object(members)) => {
+                    let known = obj.get("properties");
+                    for (name, member) in members {
+                        if matches!(known, Some(JsonValue::Object(known)) if known.contains_key(name))
+                        {
+                            continue;
+                        }
+                        self.check(
+                            schema,
+                            &schema_path,
+                            member,
+                            &instance_path.join(name.as_str()),
+                            depth,
+                            errors,
+                        );
+                    }
+                }
+                ("prefixItems", JsonValue::Array(schemas), JsonValue::Array(items)) => {
+                    for (i, (schema, item)) in schemas.iter().zip(items).enumerate() {
+                        let i = i.to_string();
+                        self.check(
+                            schema,
+                            &schema_path.join(i.as_str()),
+                            item,
+                            &instance_path.join(i),
+                            depth,
+                            errors,
+                        );
+                    }
+                }
+                ("items", schema, JsonValue::Array(items)) => {
+                    let skip = match obj.get("prefixItems") {
+                        Some(JsonValue::Array(prefix)) => prefix.len(),
+                        _ => 0,
+                    };
+                    for (i, item) in items.iter().enumerate().skip(skip) {
+                        self.check(
+                            schema,
+                            &schema_path,
+                            item,
+                            &instance_path.join(i.to_string()),
+                            depth,
+                            errors,
+                        );
+                    }
+                }
+                (
+                    "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum",
+                    limit,
+                    instance,
+                ) => {
+                    let (Some(limit), Some(v)) = (limit.as_f64(), instance.as_f64()) else {
+                        continue;
+                    };
+                    let (ok, relation) = match keyword.as_str() {
+                        "minimum" => (v >= limit, "at least"),
+                        "maximum" => (v <= limit, "at most"),
+                        "exclusiveMinimum" => (v > limit, "greater than"),
+                        _ => (v < limit, "less than"),
+                    };
+                    if !ok {
+                        fail(format!("{} must be {} {}", instance, relation, value));
+                    }
+                }
+                ("minLength" | "maxLength", limit, JsonValue::String(s)) => {
+                    let len = s.chars().count();
+                    check_len(keyword, limit, len, "characters", &mut fail);
+                }
+                ("minItems" | "maxItems", limit, JsonValue::Array(items)) => {
+                    check_len(keyword, limit, items.len(), "items", &mut fail);
+                }
+                ("minProperties" | "maxProperties", limit, JsonValue::Object(members)) => {
+                    check_len(keyword, limit, members.len(), "properties", &mut fail);
+                }
+                ("pattern", JsonValue::String(pattern), JsonValue::String(s)) => {
+                    match self.patterns.get(pattern) {
+                        Some(re) if re.is_match(s) => {}
+                        Some(_) => fail(format!("{:?} does not match pattern {:?}", s, pattern)),
+                        // only reachable for a subschema `compile` did not visit
+                        None => fail(format!("pattern {:?} was not compiled", pattern)),
+                    }
+                }
+                ("allOf", JsonValue::Array(schemas), instance) => {
+                    for (i, schema) in schemas.iter().enumerate() {
+                        self.check(
+                            schema,
+                            &schema_path.join(i.to_string()),
+                            instance,
+                            instance_path,
+                            depth,
+                            errors,
+                        );
+                    }
+                }
+                ("anyOf" | "oneOf", JsonValue::Array(schemas), instance) => {
+                    let matched = schemas
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, schema)| {
+                            let mut nested = Vec::new();
+                            let path = schema_path.join(i.to_string());
+                            self.check(schema, &path, instance, instance_path, depth, &mut nested);
+                            nested.is_empty()
+                        })
+                        .count();
+                    if keyword == "anyOf" && matched == 0 {
+                        fail("does not match any of the schemas".to_string());
+                    } else if keyword == "oneOf" && matched != 1 {
+                        fail(format!(
+                            "matches {} of the schemas, expected exactly one",
+                            matched
+                        ));
+                    }
+                }
+                ("not", schema, instance) => {
+                    let mut nested = Vec::new();
+                    self.check(
+                        schema,
+                        &schema_path,
+                        instance,
+                        instance_path,
+                        depth,
+                        &mut nested,
+                    );
+                    if nested.is_empty() {
+                        fail("must not match the schema".to_string());
+                    }
+                }
+                ("$ref", JsonValue::String(reference), instance) => {
+                    if depth >= MAX_DEPTH {
+                        fail(format!("$ref {:?} nests too deeply", reference));
+                        continue;
+                    }
+                    // checked by `compile`
+                    if let Ok(target) = self.resolve(reference) {
+                        self.check(
+                            target,
+                            &schema_path,
+                            instance,
+                            instance_path,
+                            depth + 1,
+                            errors,
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Where in the document a `$ref` such as `#/$defs/address` points.
+fn target(reference: &str) -> Result<JsonPointer, PointerError> {
+    let fragment = reference
+        .strip_prefix('#')
+        .ok_or_else(|| PointerError::MissingSlash(reference.to_string()))?;
+    JsonPointer::parse(&percent_decode(fragment))
+}
+
+fn check_type_name(name: &str) -> Result<(), String> {
+    match name {
+        "null" | "boolean" | "object" | "array" | "number" | "string" | "integer" => Ok(()),
+        name => Err(format!("unknown type {:?}", name)),
+    }
+}
+
+fn has_type(instance: &JsonValue, name: &str) -> bool {
+    match (name, instance) {
+        ("integer", JsonValue::Integer(_)) => true,
+        ("integer", JsonValue::Double(v)) => v.fract() == 0.0,
+        ("integer", JsonValue::Number(v)) => v.has_integer_value(),
+        (name, instance) => instance.kind() == name,
+    }
+}
+
+fn expected_types(expected: &JsonValue) -> String {
+    match expected {
+        JsonValue::Array(names) => names
+            .iter()
+            .filter_map(|name| match name {
+                JsonValue::String(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" or "),
+        JsonValue::String(name) => name.clone(),
+        v => v.to_string(),
+    }
+}
+
+fn check_len(
+    keyword: &str,
+    limit: &JsonValue,
+    len: usize,
+    unit: &str,
+    fail: &mut impl FnMut(String),
+) {
+    let Some(limit) = limit.as_f64() else {
+        return;
+    };
+    let len = len as f64;
+    if keyword.starts_with("min") && len < limit {
+        fail(format!(
+            "expected at least {} {}, found {}",
+            limit, unit, len
+        ));
+    } else if keyword.starts_with("max") && len > limit {
+        fail(format!(
+            "expected at most {} {}, found {}",
+            limit, unit, len
+        ));
+    }
+}
+
+/// Decode `%XX` escapes in a URI fragment; malformed escapes are kept as is.
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(decoded) if b == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at {:?}: {} (schema {:?})",
+            self.instance_path.to_string(),
+            self.message,
+            self.schema_path.to_string()
+        )
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse, parse_with, ParseOptions};
+
+    fn errors(schema: &str, instance: &str) -> Vec<String> {
+        let schema = Schema::new(parse(schema).unwrap()).unwrap();
+        match schema.validate(&parse(instance).unwrap()) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(ValidationError::to_string).collect(),
+        }
+    }
+
+    const STUDENT: &str = r##"{
+        "$defs": {
+            "mark": {"type": "number", "minimum": 0, "exclusiveMaximum": 100}
+        },
+        "type": "object",
+        "required": ["name", "marks"],
+        "additionalProperties": false,
+        "properties": {
+            "name": {"type": "string", "minLength": 1, "pattern": "^[A-Z]"},
+            "marks": {"type": "array", "items": {"$ref": "#/$defs/mark"}, "maxItems": 3},
+            "grade": {"enum": ["A", "B", "C"]},
+            "kind": {"const": "student"},
+            "age": {"type": ["integer", "null"]}
+        }
+    }"##;
+
+    #[test]
+    fn validate_should_accept_valid_instances() {
+        for instance in [
+            r#"{"name": "Jack", "marks": [90, 80.5]}"#,
+            r#"{"name": "Jack", "marks": [0], "grade": "A", "kind": "student", "age": 20.0}"#,
+            r#"{"name": "Jack", "marks": [1], "age": null}"#,
+        ] {
+            assert_eq!(
+                errors(STUDENT, instance),
+                Vec::<String>::new(),
+                "{}",
+                instance
+            );
+        }
+    }
+
+    #[test]
+    fn validate_should_report_every_violation() {
+        let instance = r#"{"name": "jack", "marks": [90, 100, -1, 5], "grade": "D", "kind": 1, "age": 1.5, "x": 1}"#;
+        assert_eq!(
+            errors(STUDENT, instance),
+            [
+                r#"at "/x": no value is allowed here (schema "/additionalProperties")"#,
+                r#"at "/name": "jack" does not match pattern "^[A-Z]" (schema "/properties/name/pattern")"#,
+                r#"at "/marks/1": 100 must be less than 100 (schema "/properties/marks/items/$ref/exclusiveMaximum")"#,
+                r#"at "/marks/2": -1 must be at least 0 (schema "/properties/marks/items/$ref/minimum")"#,
+                r#"at "/marks": expected at most 3 items, found 4 (schema "/properties/marks/maxItems")"#,
+                r#"at "/grade": "D" is not one of ["A","B","C"] (schema "/properties/grade/enum")"#,
+                r#"at "/kind": expected "student", found 1 (schema "/properties/kind/const")"#,
+                r#"at "/age": expected integer or null, found number (schema "/properties/age/type")"#,
+            ]
+        );
+        assert_eq!(
+            errors(STUDENT, "[1]"),
+            [r#"at "": expected object, found array (schema "/type")"#]
+        );
+    }
+
+    #[test]
+    fn validate_should_combine_schemas() {
+        let schema = r#"{
+            "anyOf": [{"type": "string"}, {"type": "integer"}],
+            "oneOf": [{"minimum": 5}, {"maximum": 10}],
+            "not": {"const": 7}
+        }"#;
+        assert!(errors(schema, "3").is_empty());
+        assert!(errors(schema, "12").is_empty());
+        assert_eq!(
+            errors(schema, "7"),
+            [
+                r#"at "": matches 2 of the schemas, expected exactly one (schema "/oneOf")"#,
+                r#"at "": must not match the schema (schema "/not")"#,
+            ]
+        );
+        assert_eq!(
+            errors(schema, "true"),
+            [
+                r#"at "": does not match any of the schemas (schema "/anyOf")"#,
+                r#"at "": matches 2 of the schemas, expected exactly one (schema "/oneOf")"#,
+            ]
+        );
+        assert_eq!(
+            errors(
+                r#"{"allOf": [{"prefixItems": [{"type": "string"}], "items": false}]}"#,
+                r#"["a", "b"]"#
+            ),
+            [r#"at "/1": no value is allowed here (schema "/allOf/0/items")"#]
+        );
+    }
+
+    #[test]
+    fn integer_type_should_use_exact_lossless_numbers() {
+        let schema = Schema::new(parse(r#"{"type": "integer"}"#).unwrap()).unwrap();
+        let options = ParseOptions {
+            lossless_numbers: true,
+            ..Default::default()
+        };
+        for (instance, valid) in [
+            ("9007199254740993", true),
+            ("9007199254740993.5", false),
+            ("1.0e400", true),
+            ("0.5e1", true),
+            ("1e-400", false),
+            ("-0.0", true),
+        ] {
+            let value = parse_with(instance, &options).unwrap();
+            assert_eq!(schema.is_valid(&value), valid, "{}", instance);
+        }
+    }
+
+    #[test]
+    fn schema_should_be_checked_up_front() {
+        let err = |schema: &str| Schema::new(parse(schema).unwrap()).unwrap_err().to_string();
+        assert_eq!(
+            err(r##"{"properties": {"a": {"$ref": "#/$defs/missing"}}}"##),
+            r##"Cannot resolve $ref "#/$defs/missing" at "/properties/a/$ref": No member "$defs" in the object at """##
+        );
+        assert!(err(r#"{"pattern": "("}"#).starts_with(r#"Invalid schema at "/pattern""#));
+        assert!(err(r#"{"type": "int"}"#).contains("unknown type \"int\""));
+
+        // patterns are compiled wherever a `$ref` leads, even outside `$defs`
+        let schema = r##"{"definitions": {"p": {"pattern": "^a"}}, "$ref": "#/definitions/p"}"##;
+        assert_eq!(
+            errors(schema, r#""b""#),
+            [r#"at "": "b" does not match pattern "^a" (schema "/$ref/pattern")"#]
+        );
+        assert!(
+            err(r##"{"definitions": {"p": {"pattern": "("}}, "$ref": "#/definitions/p"}"##)
+                .starts_with(r#"Invalid schema at "/definitions/p/pattern""#)
+        );
+
+        let recursive = Schema::new(parse(r##"{"$ref": "#"}"##).unwrap()).unwrap();
+        assert!(recursive.validate(&JsonValue::Null).is_err());
+        let tree = r##"{"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#"}}}}"##;
+        assert!(errors(tree, r#"{"children": [{"children": [{"children": [1]}]}]}"#).len() == 1);
+    }
+}
@@ -0,0 +1,591 @@
+use std::fmt;
+
+use serde::{
+    ser::{
+        self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+        SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize, Serializer,
+};
+
+use super::{JsonError, JsonObject, JsonValue, Number};
+
+/// Convert any `T: Serialize` into a [`JsonValue`]. Integers become
+/// [`JsonValue::Integer`], or [`JsonValue::Number`] when they don't fit an
+/// `i64`, floats [`JsonValue::Double`], maps and structs
+/// objects, sequences and tuples arrays. Enum variants other than unit ones
+/// are wrapped in an object keyed by the variant name.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<JsonValue, JsonError> {
+    value.serialize(ValueSerializer)
+}
+
+impl ser::Error for JsonError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        JsonError::Serialize(msg.to_string())
+    }
+}
+
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeVariant<SerializeObject>;
+
+    fn serialize_bool(self, v: bool) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<JsonValue, JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<JsonValue, JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<JsonValue, JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Integer(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<JsonValue, JsonError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<JsonValue, JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<JsonValue, JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<JsonValue, JsonError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<JsonValue, JsonError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<JsonValue, JsonError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<JsonValue, JsonError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<JsonValue, JsonError> {
+        let values = v.iter().map(|b| JsonValue::Integer((*b).into())).collect();
+        Ok(JsonValue::Array(values))
+    }
+
+    fn serialize_none(self) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<JsonValue, JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<JsonValue, JsonError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, JsonError> {
+        Ok(wrap_variant(variant, to_value(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, JsonError> {
+        Ok(SerializeArray(Vec::with_capacity(len.unwrap_or_default())))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeArray>, JsonError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeObject, JsonError> {
+        Ok(SerializeObject {
+            obj: JsonObject::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeObject, JsonError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeObject>, JsonError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+/// Integers beyond `i64` are kept exactly as a [`JsonValue::Number`].
+fn integer<T: TryInto<i64> + Into<Number> + Copy>(v: T) -> JsonValue {
+    v.try_into()
+        .map(JsonValue::Integer)
+        .unwrap_or_else(|_| JsonValue::Number(v.into()))
+}
+
+struct SerializeArray(Vec<JsonValue>);
+
+impl SerializeSeq for SerializeArray {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.0.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Array(self.0))
+    }
+}
+
+impl SerializeTuple for SerializeArray {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, JsonError> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SerializeArray {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, JsonError> {
+        SerializeSeq::end(self)
+    }
+}
+
+struct SerializeObject {
+    obj: JsonObject,
+    key: Option<String>,
+}
+
+impl SerializeMap for SerializeObject {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), JsonError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| JsonError::Serialize("value serialized before key".to_string()))?;
+        self.obj.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, JsonError> {
+        Ok(JsonValue::Object(self.obj))
+    }
+}
+
+impl SerializeStruct for SerializeObject {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.obj.insert(key.to_string(), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, JsonError> {
+        SerializeMap::end(self)
+    }
+}
+
+/// A tuple or struct variant, wrapped as `{"Variant": ...}` when done.
+struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+fn wrap_variant(variant: &str, value: JsonValue) -> JsonValue {
+    let mut obj = JsonObject::new();
+    obj.insert(variant.to_string(), value);
+    JsonValue::Object(obj)
+}
+
+impl SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<JsonValue, JsonError> {
+        Ok(wrap_variant(self.variant, SerializeSeq::end(self.inner)?))
+    }
+}
+
+impl SerializeStructVariant for SerializeVariant<SerializeObject> {
+    type Ok = JsonValue;
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<JsonValue, JsonError> {
+        Ok(wrap_variant(self.variant, SerializeMap::end(self.inner)?))
+    }
+}
+
+/// Object keys must be strings; integers, chars and unit variants are
+/// converted, anything else is rejected.
+struct KeySerializer;
+
+fn key_must_be_a_string() -> JsonError {
+    JsonError::Serialize("object key must be a string".to_string())
+}
+
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = JsonError;
+
+    type SerializeSeq = Impossible<String, JsonError>;
+    type SerializeTuple = Impossible<String, JsonError>;
+    type SerializeTupleStruct = Impossible<String, JsonError>;
+    type SerializeTupleVariant = Impossible<String, JsonError>;
+    type SerializeMap = Impossible<String, JsonError>;
+    type SerializeStruct = Impossible<String, JsonError>;
+    type SerializeStructVariant = Impossible<String, JsonError>;
+
+    fn serialize_str(self, v: &str) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, JsonError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, JsonError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, JsonError> {
+        Err(key_must_be_a_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::json::{from_value, parse};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Student {
+        name: String,
+        age: u8,
+        marks: Vec<f64>,
+        tags: BTreeMap<u32, String>,
+        grade: Grade,
+        nickname: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Grade {
+        Pass,
+        Fail { retry: bool },
+        Score(u8, u8),
+    }
+
+    #[test]
+    fn to_value_should_work() {
+        let student = Student {
+            name: "John Doe".into(),
+            age: 30,
+            marks: vec![90.0, 80.5],
+            tags: BTreeMap::from([(1, "a".into())]),
+            grade: Grade::Fail { retry: true },
+            nickname: None,
+        };
+        let value = to_value(&student).unwrap();
+        let expected = parse(
+            r#"{"name": "John Doe", "age": 30, "marks": [90.0, 80.5], "tags": {"1": "a"},
+                "grade": {"Fail": {"retry": true}}, "nickname": null}"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+
+        let value = to_value(&[Grade::Pass, Grade::Score(1, 2)]).unwrap();
+        assert_eq!(value.to_string(), r#"["Pass",{"Score":[1,2]}]"#);
+    }
+
+    #[test]
+    fn to_value_should_round_trip() {
+        let student = Student {
+            name: "J \"D\"".into(),
+            age: 7,
+            marks: vec![],
+            tags: BTreeMap::new(),
+            grade: Grade::Score(3, 4),
+            nickname: Some("jd".into()),
+        };
+        let back: Student = from_value(to_value(&student).unwrap()).unwrap();
+        assert_eq!(back, student);
+    }
+
+    #[test]
+    fn to_value_should_keep_wide_integers_exact() {
+        let max = to_value(&u64::MAX).unwrap();
+        assert_eq!(max, JsonValue::Number(u64::MAX.into()));
+        assert_eq!(from_value::<u64>(max).unwrap(), u64::MAX);
+        assert_eq!(
+            from_value::<i128>(to_value(&i128::MIN).unwrap()).unwrap(),
+            i128::MIN
+        );
+    }
+
+    #[test]
+    fn to_value_should_reject_non_string_keys() {
+        let map = BTreeMap::from([(vec![1], 1)]);
+        assert!(matches!(to_value(&map), Err(JsonError::Serialize(_))));
+    }
+}
@@ -0,0 +1,413 @@
+use std::str;
+
+use super::{winnow::parse_prefix, JsonError, JsonValue, ParseError, ParseOptions};
+
+/// Incremental parser for a stream of JSON values that arrives in chunks,
+/// e.g. from a socket. Values may be separated by whitespace.
+///
+/// Each byte is scanned once as it arrives, keeping only the open brackets
+/// and the token in progress, to find where the current value ends. A value is
+/// parsed once it is complete and its bytes are then dropped, so a large
+/// document fed in small chunks costs linear time. Error positions are
+/// relative to the first byte that has not been returned as a value yet.
+///
+/// ```
+/// use grammar::json::{JsonValue, StreamItem, StreamParser};
+///
+/// let mut parser = StreamParser::new();
+/// parser.feed(br#"{"id": 1}  [tr"#);
+/// assert!(matches!(parser.next_value()?, StreamItem::Value(_)));
+/// assert_eq!(parser.next_value()?, StreamItem::Incomplete);
+///
+/// parser.feed(b"ue] 42");
+/// assert_eq!(
+///     parser.next_value()?,
+///     StreamItem::Value(JsonValue::Array(vec![JsonValue::Bool(true)]))
+/// );
+/// // `42` could still be followed by more digits
+/// assert_eq!(parser.next_value()?, StreamItem::Incomplete);
+///
+/// parser.finish();
+/// assert_eq!(parser.next_value()?, StreamItem::Value(JsonValue::Integer(42)));
+/// assert_eq!(parser.next_value()?, StreamItem::End);
+/// # Ok::<(), grammar::json::JsonError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct StreamParser {
+    buf: Vec<u8>,
+    /// Start of the bytes not yet returned as a value.
+    pos: usize,
+    /// How far the scanner has got.
+    scanned: usize,
+    /// End of the pending value, once the scanner has found it.
+    end: Option<usize>,
+    scanner: Scanner,
+    finished: bool,
+    options: ParseOptions,
+}
+
+/// What [`StreamParser::next_value`] found in the input fed so far.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamItem {
+    Value(JsonValue),
+    /// The pending bytes are not a whole value yet; feed more.
+    Incomplete,
+    /// Every value has been returned and the input is finished.
+    End,
+}
+
+/// Where a value ends, found without parsing it: strings, comments and the
+/// nesting of brackets are tracked, anything else is left to the parser.
+#[derive(Debug, Default)]
+struct Scanner {
+    /// The closing bracket of every open container, innermost last.
+    closers: Vec<u8>,
+    token: Token,
+    /// Whether a byte of the value itself, not just comments, has been seen.
+    started: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    #[default]
+    None,
+    String {
+        quote: u8,
+        escaped: bool,
+    },
+    /// A number or literal at the top level, which ends at the next delimiter.
+    Scalar,
+    /// A `/` that may start a comment.
+    Slash,
+    LineComment,
+    BlockComment {
+        star: bool,
+    },
+}
+
+/// What the scanner made of one byte.
+enum Scan {
+    Continue,
+    /// Whitespace or a comment before the value.
+    Skip,
+    /// The value ends after this byte.
+    EndAfter,
+    /// The value ended before this byte.
+    EndBefore,
+}
+
+impl StreamParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Append the next chunk of input. Chunks may split values, and UTF-8
+    /// characters, at any byte.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        // drop returned values once they make up half the buffer, so the
+        // pending bytes are moved a bounded number of times
+        if self.pos > 0 && self.pos * 2 >= self.buf.len() {
+            self.buf.drain(..self.pos);
+            self.scanned -= self.pos;
+            self.end = self.end.map(|end| end - self.pos);
+            self.pos = 0;
+        }
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Mark the end of input: whatever is pending must now form whole values.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// The next complete value, [`StreamItem::Incomplete`] while the input
+    /// seen so far ends inside one, or [`StreamItem::End`] after
+    /// [`finish`](Self::finish) once every value has been returned.
+    pub fn next_value(&mut self) -> Result<StreamItem, JsonError> {
+        let end = match self.end.or_else(|| self.scan()) {
+            Some(end) => end,
+            None if self.scanned - self.pos > self.options.limits.max_input_len => {
+                // let the parser report the limit rather than buffer forever
+                return match self.parse(self.scanned, true)? {
+                    Some(value) => Ok(StreamItem::Value(value)),
+                    None => Ok(StreamItem::Incomplete),
+                };
+            }
+            None if !self.finished => return Ok(StreamItem::Incomplete),
+            None if !self.scanner.started
+                && matches!(self.scanner.token, Token::None | Token::LineComment) =>
+            {
+                return Ok(StreamItem::End);
+            }
+            None => self.buf.len(),
+        };
+        self.end = Some(end);
+        match self.parse(end, false)? {
+            Some(value) => Ok(StreamItem::Value(value)),
+            None => Ok(StreamItem::Incomplete),
+        }
+    }
+
+    /// Scan the new bytes until the pending value ends, returning its end.
+    fn scan(&mut self) -> Option<usize> {
+        while self.scanned < self.buf.len() {
+            let i = self.scanned;
+            match self.scanner.scan(self.buf[i], &self.options) {
+                Scan::Continue => self.scanned += 1,
+                Scan::Skip => {
+                    self.scanned += 1;
+                    self.pos = self.scanned;
+                }
+                Scan::EndAfter => return Some(i + 1),
+                Scan::EndBefore => return Some(i),
+            }
+        }
+        None
+    }
+
+    /// Parse the pending value up to `end` and drop its bytes.
+    fn parse(&mut self, end: usize, partial: bool) -> Result<Option<JsonValue>, JsonError> {
+        let pending = &self.buf[self.pos..end];
+        let text = match str::from_utf8(pending) {
+            Ok(text) => text,
+            // a split character may still be completed by the next chunk
+            Err(e) if partial && e.error_len().is_none() => {
+                str::from_utf8(&pending[..e.valid_up_to()]).unwrap_or_default()
+            }
+            Err(e) => {
+                let text = str::from_utf8(&pending[..e.valid_up_to()]).unwrap_or_default();
+                let err = ParseError::new(text, text.len()).with_message("invalid UTF-8");
+                return Err(err.into());
+            }
+        };
+        let Some((value, consumed)) = parse_prefix(text, partial, &self.options)? else {
+            return Ok(None);
+        };
+        // scan again from the end of the value, which only differs from
+        // `end` for something like `1-2` that the scanner took as one token
+        self.pos += consumed;
+        self.scanned = self.pos;
+        self.end = None;
+        self.scanner = Scanner::default();
+        Ok(Some(value))
+    }
+}
+
+impl Scanner {
+    fn scan(&mut self, b: u8, options: &ParseOptions) -> Scan {
+        let top = self.closers.is_empty();
+        match self.token {
+            Token::String { quote, escaped } => {
+                self.token = match (escaped, b) {
+                    (true, _) => Token::String {
+                        quote,
+                        escaped: false,
+                    },
+                    (false, b'\\') => Token::String {
+                        quote,
+                        escaped: true,
+                    },
+                    (false, b) if b == quote => Token::None,
+                    _ => self.token,
+                };
+                match self.token == Token::None && top {
+                    true => Scan::EndAfter,
+                    false => Scan::Continue,
+                }
+            }
+            Token::Scalar if is_delimiter(b) => Scan::EndBefore,
+            Token::Scalar => Scan::Continue,
+            Token::Slash => match b {
+                b'/' => self.comment(Token::LineComment),
+                b'*' => self.comment(Token::BlockComment { star: false }),
+                // not a comment, so the parser will reject it
+                _ => Scan::EndAfter,
+            },
+            Token::LineComment if b == b'\n' => self.comment(Token::None),
+            Token::LineComment => self.comment(Token::LineComment),
+            Token::BlockComment { star: true } if b == b'/' => self.comment(Token::None),
+            Token::BlockComment { .. } => self.comment(Token::BlockComment { star: b == b'*' }),
+            Token::None => match b {
+                b' ' | b'\t' | b'\n' | b'\r' if !self.started => Scan::Skip,
+                b'/' if options.dialect.comments => self.comment(Token::Slash),
+                b'"' => self.start(Token::String {
+                    quote: b'"',
+                    escaped: false,
+                }),
+                b'\'' if options.dialect.single_quotes => self.start(Token::String {
+                    quote: b'\'',
+                    escaped: false,
+                }),
+                b'[' | b'{' => {
+                    self.started = true;
+                    self.closers.push(if b == b'[' { b']' } else { b'}' });
+                    // deeper than allowed: end here and let the parser say so
+                    match self.closers.len() > options.limits.max_depth {
+                        true => Scan::EndAfter,
+                        false => Scan::Continue,
+                    }
+                }
+                b']' | b'}' => match self.closers.pop() {
+                    Some(closer) if closer == b && !self.closers.is_empty() => Scan::Continue,
+                    // the outermost container closed, or a stray or
+                    // mismatched bracket ends the value early for the parser
+                    // to reject
+                    _ => {
+                        self.started = true;
+                        Scan::EndAfter
+                    }
+                },
+                _ if top && !is_delimiter(b) => self.start(Token::Scalar),
+                _ if top => {
+                    self.started = true;
+                    Scan::EndAfter
+                }
+                _ => Scan::Continue,
+            },
+        }
+    }
+
+    fn start(&mut self, token: Token) -> Scan {
+        self.started = true;
+        self.token = token;
+        Scan::Continue
+    }
+
+    /// Comments before the value are skipped like whitespace.
+    fn comment(&mut self, token: Token) -> Scan {
+        self.token = token;
+        match self.started || token != Token::None {
+            true => Scan::Continue,
+            false => Scan::Skip,
+        }
+    }
+}
+
+/// Bytes that end a number or literal.
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b' ' | b'\t'
+            | b'\n'
+            | b'\r'
+            | b'['
+            | b']'
+            | b'{'
+            | b'}'
+            | b','
+            | b':'
+            | b'"'
+            | b'\''
+            | b'/'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every value in `input` fed in chunks of `size`, then finished.
+    fn values(input: &str, size: usize, options: &ParseOptions) -> Vec<JsonValue> {
+        let mut parser = StreamParser::with_options(options.clone());
+        let mut values = Vec::new();
+        for chunk in input.as_bytes().chunks(size) {
+            parser.feed(chunk);
+            while let StreamItem::Value(v) = parser.next_value().unwrap() {
+                values.push(v);
+            }
+        }
+        parser.finish();
+        while let StreamItem::Value(v) = parser.next_value().unwrap() {
+            values.push(v);
+        }
+        assert_eq!(parser.next_value().unwrap(), StreamItem::End);
+        values
+    }
+
+    #[test]
+    fn stream_parser_should_yield_values_across_chunks() {
+        let input = r#"{"name": "中文", "marks": [90, -80.5]} [true, null] "x\"]" 42"#;
+        let expected = [
+            crate::json::parse(r#"{"name": "中文", "marks": [90, -80.5]}"#).unwrap(),
+            JsonValue::Array(vec![JsonValue::Bool(true), JsonValue::Null]),
+            JsonValue::String("x\"]".into()),
+            JsonValue::Integer(42),
+        ];
+
+        // every chunk size, including ones that split multi-byte characters
+        for size in 1..=input.len() {
+            assert_eq!(
+                values(input, size, &ParseOptions::default()),
+                expected,
+                "chunk size {}",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn stream_parser_should_wait_for_comments() {
+        let input = "/* head */ [1, /* ] */ 2, // b]\n 3,] // tail";
+        let options = ParseOptions {
+            dialect: crate::json::Dialect::JSONC,
+            ..Default::default()
+        };
+        for size in 1..=input.len() {
+            assert_eq!(
+                values(input, size, &options),
+                [crate::json::parse("[1, 2, 3]").unwrap()],
+                "chunk size {}",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn stream_parser_should_scan_each_byte_once() {
+        let mut parser = StreamParser::new();
+        parser.feed(b"[");
+        for _ in 0..10_000 {
+            parser.feed(br#"{"a": [1, "]"]}, "#);
+            assert_eq!(parser.next_value().unwrap(), StreamItem::Incomplete);
+            assert_eq!(parser.scanned, parser.buf.len());
+        }
+        parser.feed(b"null]");
+        let StreamItem::Value(JsonValue::Array(values)) = parser.next_value().unwrap() else {
+            panic!("expected array");
+        };
+        assert_eq!(values.len(), 10_001);
+    }
+
+    #[test]
+    fn stream_parser_should_report_errors() {
+        let mut parser = StreamParser::new();
+        parser.feed(b"[1, 2] [1 2]");
+        assert!(matches!(parser.next_value(), Ok(StreamItem::Value(_))));
+        match parser.next_value() {
+            Err(JsonError::Parse(e)) => assert_eq!((e.offset, e.found), (3, Some('2'))),
+            ret => panic!("expected parse error, got {:?}", ret),
+        }
+
+        let mut parser = StreamParser::new();
+        parser.feed(br#"{"a": "#);
+        assert_eq!(parser.next_value().unwrap(), StreamItem::Incomplete);
+        parser.finish();
+        assert!(parser.next_value().is_err());
+
+        let mut parser = StreamParser::new();
+        parser.feed(b"[\"\xff\"]");
+        assert!(parser.next_value().is_err());
+
+        // mismatched brackets fail without waiting for more input
+        let mut parser = StreamParser::new();
+        parser.feed(b"[1}");
+        assert!(parser.next_value().is_err());
+    }
+}
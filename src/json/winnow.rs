@@ -0,0 +1,1181 @@
+use std::{borrow::Cow, cell::Cell, hash::Hash};
+
+use winnow::{
+    ascii::{digit0, digit1, multispace0},
+    combinator::{
+        alt, cut_err, eof, not, opt, preceded, repeat, separated, separated_pair, terminated, trace,
+    },
+    error::{
+        ContextError, ErrMode, ErrorKind, FromExternalError, Needed, ParserError, StrContext,
+        StrContextValue,
+    },
+    stream::{AsChar, Partial, Stream, StreamIsPartial},
+    token::{one_of, take_till, take_until, take_while},
+    PResult, Parser,
+};
+
+use indexmap::{map::Entry, IndexMap};
+use thiserror::Error;
+
+use super::{
+    borrowed, number, DuplicateKeys, JsonError, JsonValue, Limit, Number, ParseError, ParseOptions,
+    PathSegment,
+};
+
+pub fn parse(input: &str) -> Result<JsonValue, JsonError> {
+    parse_with(input, &ParseOptions::default())
+}
+
+pub fn parse_with(input: &str, options: &ParseOptions) -> Result<JsonValue, JsonError> {
+    parse_document(input, options)
+}
+
+/// Parse into a [`borrowed::JsonValue`] that borrows unescaped strings from `input`.
+pub fn parse_borrowed(input: &str) -> Result<borrowed::JsonValue<'_>, JsonError> {
+    parse_borrowed_with(input, &ParseOptions::default())
+}
+
+pub fn parse_borrowed_with<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<borrowed::JsonValue<'a>, JsonError> {
+    parse_document(input, options)
+}
+
+fn parse_document<'a, V: Value<'a>>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<V, JsonError> {
+    options.limits.check_input_len(input)?;
+    let mut rest = Partial::new(input);
+    let _ = rest.complete();
+    let document = |i: &mut Input<'a>| {
+        skip_space(i, options)?;
+        let v = parse_value(i, options, 0)?;
+        skip_space(i, options)?;
+        eof.context(description("end of input")).parse_next(i)?;
+        Ok(v)
+    };
+    document(&mut rest).map_err(|e| to_json_error(input, rest.len(), e))
+}
+
+/// Parse one value from the front of `input` and return it together with the
+/// number of bytes consumed. With `partial` set, `Ok(None)` means the value
+/// may continue past the end of `input`. The input length limit applies to
+/// the value, not to whatever follows it.
+pub(crate) fn parse_prefix(
+    input: &str,
+    partial: bool,
+    options: &ParseOptions,
+) -> Result<Option<(JsonValue, usize)>, JsonError> {
+    let mut rest = Partial::new(input);
+    if !partial {
+        let _ = rest.complete();
+    }
+    let value = |i: &mut Input<'_>| {
+        skip_space(i, options)?;
+        parse_value(i, options, 0)
+    };
+    match value(&mut rest) {
+        Ok(v) => {
+            let consumed = input.len() - rest.len();
+            options.limits.check_input_len(&input[..consumed])?;
+            Ok(Some((v, consumed)))
+        }
+        Err(ErrMode::Incomplete(_)) => options.limits.check_input_len(input).map(|()| None),
+        Err(e) => Err(to_json_error(input, rest.len(), e)),
+    }
+}
+
+/// Byte offset of the value at `path` in `input`, used to point
+/// deserialization errors into the source text.
+pub(crate) fn locate(input: &str, options: &ParseOptions, path: &[PathSegment]) -> Option<usize> {
+    let root: Located = parse_document(input, options).ok()?;
+    let mut node = &root;
+    for segment in path {
+        node = match (segment, &node.children) {
+            (PathSegment::Index(i), Children::Array(values)) => values.get(*i)?,
+            (PathSegment::Key(key), Children::Object(obj)) => obj.get(key.as_str())?,
+            _ => return None,
+        };
+    }
+    Some(input.len() - node.remaining)
+}
+
+/// Input of every parser here. Complete documents go through a [`Partial`]
+/// marked as complete, so the same parsers also serve the incremental
+/// [`StreamParser`](super::StreamParser).
+type Input<'a> = Partial<&'a str>;
+
+/// What the value parsers build, so one grammar produces both the owned
+/// [`JsonValue`] and the borrowed [`borrowed::JsonValue`].
+trait Value<'a>: Sized {
+    type Key: Hash + Eq + AsRef<str>;
+
+    fn key(s: Cow<'a, str>) -> Self::Key;
+    fn null() -> Self;
+    fn bool(v: bool) -> Self;
+    /// A number as written in the document, see [`parse_number`].
+    fn number(lexeme: &str, options: &ParseOptions) -> Self;
+    /// A number without a JSON form, such as `NaN`.
+    fn double(v: f64) -> Self;
+    fn string(v: Cow<'a, str>) -> Self;
+    fn array(values: Vec<Self>) -> Self;
+    fn object(obj: IndexMap<Self::Key, Self>) -> Self;
+
+    /// Called with the length of the input left where the value starts.
+    fn with_remaining(self, _remaining: usize) -> Self {
+        self
+    }
+}
+
+impl<'a> Value<'a> for JsonValue {
+    type Key = String;
+
+    fn key(s: Cow<'a, str>) -> String {
+        s.into_owned()
+    }
+    fn null() -> Self {
+        JsonValue::Null
+    }
+    fn bool(v: bool) -> Self {
+        JsonValue::Bool(v)
+    }
+    fn number(lexeme: &str, options: &ParseOptions) -> Self {
+        match options.lossless_numbers {
+            true => JsonValue::Number(Number::from_lexeme(lexeme)),
+            false => number::native(lexeme, JsonValue::Integer, JsonValue::Double),
+        }
+    }
+    fn double(v: f64) -> Self {
+        JsonValue::Double(v)
+    }
+    fn string(v: Cow<'a, str>) -> Self {
+        JsonValue::String(v.into_owned())
+    }
+    fn array(values: Vec<Self>) -> Self {
+        JsonValue::Array(values)
+    }
+    fn object(obj: IndexMap<String, Self>) -> Self {
+        JsonValue::Object(obj)
+    }
+}
+
+/// Only the shape of a document and where each value starts, see [`locate`].
+struct Located<'a> {
+    remaining: usize,
+    children: Children<'a>,
+}
+
+enum Children<'a> {
+    None,
+    Array(Vec<Located<'a>>),
+    Object(IndexMap<Cow<'a, str>, Located<'a>>),
+}
+
+impl<'a> Located<'a> {
+    fn new(children: Children<'a>) -> Self {
+        Self {
+            remaining: 0,
+            children,
+        }
+    }
+}
+
+impl<'a> Value<'a> for Located<'a> {
+    type Key = Cow<'a, str>;
+
+    fn key(s: Cow<'a, str>) -> Cow<'a, str> {
+        s
+    }
+    fn null() -> Self {
+        Located::new(Children::None)
+    }
+    fn bool(_: bool) -> Self {
+        Located::new(Children::None)
+    }
+    fn number(_: &str, _: &ParseOptions) -> Self {
+        Located::new(Children::None)
+    }
+    fn double(_: f64) -> Self {
+        Located::new(Children::None)
+    }
+    fn string(_: Cow<'a, str>) -> Self {
+        Located::new(Children::None)
+    }
+    fn array(values: Vec<Self>) -> Self {
+        Located::new(Children::Array(values))
+    }
+    fn object(obj: IndexMap<Cow<'a, str>, Self>) -> Self {
+        Located::new(Children::Object(obj))
+    }
+    fn with_remaining(mut self, remaining: usize) -> Self {
+        self.remaining = remaining;
+        self
+    }
+}
+
+impl<'a> Value<'a> for borrowed::JsonValue<'a> {
+    type Key = Cow<'a, str>;
+
+    fn key(s: Cow<'a, str>) -> Cow<'a, str> {
+        s
+    }
+    fn null() -> Self {
+        borrowed::JsonValue::Null
+    }
+    fn bool(v: bool) -> Self {
+        borrowed::JsonValue::Bool(v)
+    }
+    fn number(lexeme: &str, options: &ParseOptions) -> Self {
+        match options.lossless_numbers {
+            true => borrowed::JsonValue::Number(Number::from_lexeme(lexeme)),
+            false => number::native(
+                lexeme,
+                borrowed::JsonValue::Integer,
+                borrowed::JsonValue::Double,
+            ),
+        }
+    }
+    fn double(v: f64) -> Self {
+        borrowed::JsonValue::Double(v)
+    }
+    fn string(v: Cow<'a, str>) -> Self {
+        borrowed::JsonValue::String(v)
+    }
+    fn array(values: Vec<Self>) -> Self {
+        borrowed::JsonValue::Array(values)
+    }
+    fn object(obj: borrowed::JsonObject<'a>) -> Self {
+        borrowed::JsonValue::Object(obj)
+    }
+}
+
+fn to_json_error(input: &str, rest_len: usize, e: ErrMode<ContextError>) -> JsonError {
+    let offset = input.len() - rest_len;
+    let e = match e {
+        ErrMode::Backtrack(e) | ErrMode::Cut(e) => e,
+        ErrMode::Incomplete(_) => {
+            let err = ParseError::new(input, input.len()).with_message("incomplete input");
+            return err.into();
+        }
+    };
+    if let Some(&LimitError(limit, max)) = e.cause().and_then(|cause| cause.downcast_ref()) {
+        return JsonError::LimitExceeded { limit, max, offset };
+    }
+    match e
+        .cause()
+        .and_then(|cause| cause.downcast_ref::<DuplicateKey>())
+    {
+        Some(DuplicateKey(key)) => JsonError::DuplicateKey {
+            key: key.clone(),
+            offset,
+        },
+        None => to_parse_error(input, offset, &e).into(),
+    }
+}
+
+/// Turn the innermost contexts of a winnow error into a [`ParseError`].
+///
+/// Contexts are pushed while the error bubbles up, so the first `Label` names
+/// the construct that failed and the `Expected` values before it are what it
+/// would have accepted; everything after belongs to enclosing values.
+pub(crate) fn to_parse_error(input: &str, offset: usize, e: &ContextError) -> ParseError {
+    let mut err = ParseError::new(input, offset);
+    let mut expected = Vec::new();
+    for context in e.context() {
+        match context {
+            StrContext::Label(label) => {
+                err = err.with_label(*label);
+                break;
+            }
+            StrContext::Expected(value) => expected.push(value.to_string()),
+            _ => {}
+        }
+    }
+    if let Some(cause) = e.cause() {
+        err = err.with_message(cause.to_string());
+    }
+    err.with_expected(expected)
+}
+
+#[derive(Debug, Error)]
+#[error("duplicate key {0:?}")]
+struct DuplicateKey(String);
+
+#[derive(Debug, Error)]
+#[error("{0} exceeds {1}")]
+struct LimitError(Limit, usize);
+
+/// Fail with `limit` at the position `input` is reset to.
+fn exceeded<'a, T>(
+    input: &mut Input<'a>,
+    start: &<Input<'a> as Stream>::Checkpoint,
+    limit: Limit,
+    max: usize,
+) -> PResult<T> {
+    input.reset(start);
+    let err = LimitError(limit, max);
+    Err(ErrMode::Cut(ContextError::from_external_error(
+        input,
+        ErrorKind::Verify,
+        err,
+    )))
+}
+
+fn expected(value: impl Into<StrContextValue>) -> StrContext {
+    StrContext::Expected(value.into())
+}
+
+fn description(s: &'static str) -> StrContext {
+    StrContext::Expected(StrContextValue::Description(s))
+}
+
+fn parse_null(input: &mut Input<'_>) -> PResult<()> {
+    "null".value(()).parse_next(input)
+}
+
+fn parse_bool(input: &mut Input<'_>) -> PResult<bool> {
+    alt(("true", "false")).parse_to().parse_next(input)
+}
+
+/// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`, returned as
+/// written so no precision is lost before the caller decides how to store it.
+fn parse_number<'a>(input: &mut Input<'a>) -> PResult<&'a str> {
+    let int = alt(("0", (one_of('1'..='9'), digit0).recognize()));
+    let frac = ('.', cut_err(digit1).context(description("digit")));
+    let exp = (
+        one_of(['e', 'E']),
+        opt(one_of(['+', '-'])),
+        cut_err(digit1).context(description("digit")),
+    );
+    (opt('-'), int, opt(frac), opt(exp))
+        .recognize()
+        .parse_next(input)
+}
+
+/// Whether all of `s` is a JSON number.
+pub(crate) fn is_number(s: &str) -> bool {
+    let mut input = Partial::new(s);
+    let _ = input.complete();
+    parse_number(&mut input).is_ok() && input.is_empty()
+}
+
+/// `Infinity` or `NaN`, either with a `-`, with [`Dialect::non_finite_numbers`].
+///
+/// [`Dialect::non_finite_numbers`]: super::Dialect::non_finite_numbers
+fn parse_non_finite(input: &mut Input<'_>, options: &ParseOptions) -> PResult<f64> {
+    if !options.dialect.non_finite_numbers {
+        return Err(ErrMode::Backtrack(ContextError::new()));
+    }
+    alt((
+        "Infinity".value(f64::INFINITY),
+        "-Infinity".value(f64::NEG_INFINITY),
+        "NaN".value(f64::NAN),
+        "-NaN".value(f64::NAN),
+    ))
+    .parse_next(input)
+}
+
+/// A number after an explicit `+`, such as `+1`, `+0x1F` or `+Infinity`, with
+/// [`Dialect::plus_signs`].
+///
+/// [`Dialect::plus_signs`]: super::Dialect::plus_signs
+fn parse_plus<'a, V: Value<'a>>(input: &mut Input<'a>, options: &ParseOptions) -> PResult<V> {
+    if !options.dialect.plus_signs {
+        return Err(ErrMode::Backtrack(ContextError::new()));
+    }
+    // the other number parsers accept a `-`, which must not follow the `+`
+    ('+', not('-')).parse_next(input)?;
+    cut_err(alt((
+        (|i: &mut Input<'a>| parse_non_finite(i, options)).map(V::double),
+        (|i: &mut Input<'a>| parse_hex(i, options)).map(|n| V::number(&n, options)),
+        parse_number.map(|n| V::number(n, options)),
+    )))
+    .context(description("number"))
+    .parse_next(input)
+}
+
+/// A hexadecimal integer such as `-0x1F` with [`Dialect::hex_numbers`],
+/// returned as a decimal lexeme.
+///
+/// [`Dialect::hex_numbers`]: super::Dialect::hex_numbers
+fn parse_hex(input: &mut Input<'_>, options: &ParseOptions) -> PResult<String> {
+    if !options.dialect.hex_numbers {
+        return Err(ErrMode::Backtrack(ContextError::new()));
+    }
+    let digits = take_while(1.., AsChar::is_hex_digit)
+        .try_map(|digits| u128::from_str_radix(digits, 16))
+        .context(description("hex digit"));
+    (opt('-'), preceded(alt(("0x", "0X")), cut_err(digits)))
+        .map(|(sign, v)| format!("{}{}", sign.map_or("", |_| "-"), v))
+        .parse_next(input)
+}
+
+/// Whitespace, and comments when the dialect allows them.
+fn skip_space(input: &mut Input<'_>, options: &ParseOptions) -> PResult<()> {
+    multispace0.parse_next(input)?;
+    if options.dialect.comments {
+        repeat(0.., (parse_comment, multispace0))
+            .map(|()| ())
+            .parse_next(input)?;
+    }
+    Ok(())
+}
+
+fn parse_comment<'a>(input: &mut Input<'a>) -> PResult<&'a str> {
+    let line = ("//", till_line_end);
+    let block = (
+        "/*",
+        cut_err((take_until(0.., "*/"), "*/")).context(expected("*/")),
+    );
+    alt((line.recognize(), block.recognize()))
+        .context(StrContext::Label("comment"))
+        .parse_next(input)
+}
+
+/// The rest of the line. `take_till` on a `Partial` stream asks for more
+/// input at the end even once it is complete, so a comment on the last line
+/// needs this instead.
+fn till_line_end<'a>(input: &mut Input<'a>) -> PResult<&'a str> {
+    match input.find('\n') {
+        Some(n) => Ok(input.next_slice(n)),
+        None if input.is_partial() => Err(ErrMode::Incomplete(Needed::new(1))),
+        None => Ok(input.finish()),
+    }
+}
+
+/// `c` surrounded by whitespace and, when allowed, comments.
+fn sep<'a, 'o>(
+    mut c: char,
+    options: &'o ParseOptions,
+) -> impl Parser<Input<'a>, (), ContextError> + 'o {
+    move |input: &mut Input<'a>| {
+        skip_space(input, options)?;
+        c.parse_next(input)?;
+        skip_space(input, options)
+    }
+}
+
+/// The closing bracket of a container, after an optional trailing comma when
+/// the dialect allows one. Closing brackets don't eat trailing whitespace, so
+/// a partial input can end right after a complete value.
+fn close<'a, 'o>(
+    c: char,
+    options: &'o ParseOptions,
+) -> impl Parser<Input<'a>, (), ContextError> + 'o {
+    move |input: &mut Input<'a>| {
+        skip_space(input, options)?;
+        if options.dialect.trailing_commas {
+            opt((',', |i: &mut Input<'a>| skip_space(i, options))).parse_next(input)?;
+        }
+        c.void().parse_next(input)
+    }
+}
+
+/// A double-quoted string, see [`parse_quoted`].
+fn parse_string<'a>(input: &mut Input<'a>) -> PResult<Cow<'a, str>> {
+    parse_quoted(input, '"')
+}
+
+/// A double-quoted string, or a single-quoted one when the dialect allows it.
+fn parse_any_string<'a>(input: &mut Input<'a>, options: &ParseOptions) -> PResult<Cow<'a, str>> {
+    let start = input.checkpoint();
+    let s = match options.dialect.single_quotes {
+        true => alt((parse_string, |i: &mut Input<'a>| parse_quoted(i, '\''))).parse_next(input)?,
+        false => parse_string(input)?,
+    };
+    check_string_len(input, &start, s, options)
+}
+
+/// An object key: a string, or an identifier when the dialect allows it.
+fn parse_key<'a>(input: &mut Input<'a>, options: &ParseOptions) -> PResult<Cow<'a, str>> {
+    let start = input.checkpoint();
+    let mut string = |i: &mut Input<'a>| parse_any_string(i, options);
+    let key = match options.dialect.unquoted_keys {
+        true => alt((string, parse_identifier.map(Cow::Borrowed))).parse_next(input)?,
+        false => string.parse_next(input)?,
+    };
+    check_string_len(input, &start, key, options)
+}
+
+/// Fail at `start` when the string parsed from there is too long.
+fn check_string_len<'a>(
+    input: &mut Input<'a>,
+    start: &<Input<'a> as Stream>::Checkpoint,
+    s: Cow<'a, str>,
+    options: &ParseOptions,
+) -> PResult<Cow<'a, str>> {
+    let max = options.limits.max_string_len;
+    match s.len() > max {
+        true => exceeded(input, start, Limit::StringLength, max),
+        false => Ok(s),
+    }
+}
+
+fn parse_identifier<'a>(input: &mut Input<'a>) -> PResult<&'a str> {
+    let is_start = |c: char| c.is_ascii_alphabetic() || c == '_' || c == '$';
+    let is_continue = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+    (one_of(is_start), take_while(0.., is_continue))
+        .recognize()
+        .parse_next(input)
+}
+
+/// A string delimited by `quote`. A string without escapes is a single
+/// literal fragment and is borrowed from the input; decoding an escape
+/// switches to an owned copy.
+fn parse_quoted<'a>(input: &mut Input<'a>, quote: char) -> PResult<Cow<'a, str>> {
+    let content = repeat(0.., |i: &mut Input<'a>| parse_string_fragment(i, quote)).fold(
+        || Cow::Borrowed(""),
+        |mut s: Cow<'a, str>, fragment| {
+            match fragment {
+                StringFragment::Literal(v) if s.is_empty() => s = Cow::Borrowed(v),
+                StringFragment::Literal(v) => s.to_mut().push_str(v),
+                StringFragment::Escaped(c) => s.to_mut().push(c),
+            }
+            s
+        },
+    );
+    let close = quote.context(expected(quote));
+    preceded(
+        quote,
+        cut_err(terminated(content, close)).context(StrContext::Label("string")),
+    )
+    .parse_next(input)
+}
+
+enum StringFragment<'a> {
+    Literal(&'a str),
+    Escaped(char),
+}
+
+/// A run of unescaped characters, or a single escape sequence. Raw control
+/// characters are not allowed in JSON strings, so they end the fragment and
+/// the closing quote then fails to match.
+fn parse_string_fragment<'a>(input: &mut Input<'a>, quote: char) -> PResult<StringFragment<'a>> {
+    alt((
+        // the explicit upper bound picks winnow's bounded implementation, which
+        // honours a `Partial` marked as complete; `1..` always reports
+        // `Incomplete` at the end of a `Partial` input
+        take_till(1..=usize::MAX, (quote, '\\', '\0'..='\u{1f}')).map(StringFragment::Literal),
+        preceded('\\', cut_err(|i: &mut Input<'_>| parse_escape(i, quote)))
+            .map(StringFragment::Escaped),
+    ))
+    .parse_next(input)
+}
+
+/// The character after a backslash; `\'` is only an escape in single-quoted
+/// strings.
+fn parse_escape(input: &mut Input<'_>, quote: char) -> PResult<char> {
+    alt((
+        '"'.value('"'),
+        '\\'.value('\\'),
+        '/'.value('/'),
+        'b'.value('\u{08}'),
+        'f'.value('\u{0c}'),
+        'n'.value('\n'),
+        'r'.value('\r'),
+        't'.value('\t'),
+        preceded('u', cut_err(parse_unicode_escape)),
+        '\''.verify(|_| quote == '\'').value('\''),
+    ))
+    .context(description("escape sequence"))
+    .parse_next(input)
+}
+
+/// `\uXXXX` with the `\u` already consumed. Code points outside the BMP are
+/// written as a UTF-16 surrogate pair; a lone surrogate is rejected.
+fn parse_unicode_escape(input: &mut Input<'_>) -> PResult<char> {
+    alt((
+        parse_hex4.verify(|code| !(0xD800..=0xDFFF).contains(code)),
+        separated_pair(
+            parse_hex4.verify(|high| (0xD800..=0xDBFF).contains(high)),
+            "\\u",
+            parse_hex4.verify(|low| (0xDC00..=0xDFFF).contains(low)),
+        )
+        .map(|(high, low)| 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)),
+    ))
+    .verify_map(char::from_u32)
+    .context(description("4 hex digits"))
+    .context(description("UTF-16 surrogate pair"))
+    .context(StrContext::Label("unicode escape"))
+    .parse_next(input)
+}
+
+fn parse_hex4(input: &mut Input<'_>) -> PResult<u32> {
+    take_while(4, AsChar::is_hex_digit)
+        .try_map(|s| u32::from_str_radix(s, 16))
+        .parse_next(input)
+}
+
+/// The opening bracket of a container whose values are at `depth`, failing
+/// with [`Limit::Depth`] at the bracket when that is too deep.
+fn open(input: &mut Input<'_>, mut c: char, options: &ParseOptions, depth: usize) -> PResult<()> {
+    skip_space(input, options)?;
+    let start = input.checkpoint();
+    c.parse_next(input)?;
+    let max = options.limits.max_depth;
+    if depth > max {
+        return exceeded(input, &start, Limit::Depth, max);
+    }
+    skip_space(input, options)
+}
+
+/// Elements and members are counted as they are parsed, so an oversized
+/// container fails at its first extra entry.
+fn count_entry(
+    input: &mut Input<'_>,
+    count: &Cell<usize>,
+    limit: Limit,
+    max: usize,
+) -> PResult<()> {
+    count.set(count.get() + 1);
+    match count.get() > max {
+        true => exceeded(input, &input.checkpoint(), limit, max),
+        false => Ok(()),
+    }
+}
+
+fn parse_array<'a, V: Value<'a>>(
+    input: &mut Input<'a>,
+    options: &ParseOptions,
+    depth: usize,
+) -> PResult<Vec<V>> {
+    let close_array = close(']', options)
+        .context(expected(','))
+        .context(expected(']'));
+    let count = Cell::new(0);
+    let element = |i: &mut Input<'a>| {
+        // with trailing commas a `]` after the comma ends the array instead
+        if options.dialect.trailing_commas && i.starts_with(']') {
+            return Err(ErrMode::Backtrack(ContextError::new()));
+        }
+        count_entry(i, &count, Limit::ArrayLength, options.limits.max_array_len)?;
+        cut_err(|i: &mut Input<'a>| parse_value(i, options, depth + 1)).parse_next(i)
+    };
+    let parse_values = separated(1.., element, sep(',', options));
+    let empty = preceded(|i: &mut Input<'a>| skip_space(i, options), ']');
+    let body = alt((
+        empty.map(|_| Vec::new()),
+        terminated(parse_values, close_array),
+    ));
+    let values = preceded(
+        |i: &mut Input<'a>| open(i, '[', options, depth + 1),
+        cut_err(body).context(StrContext::Label("array")),
+    )
+    .parse_next(input)?;
+    Ok(values)
+}
+
+fn parse_object<'a, V: Value<'a>>(
+    input: &mut Input<'a>,
+    options: &ParseOptions,
+    depth: usize,
+) -> PResult<IndexMap<V::Key, V>> {
+    let close_object = close('}', options)
+        .context(expected(','))
+        .context(expected('}'));
+    let count = Cell::new(0);
+    let member = |i: &mut Input<'a>| {
+        let max = options.limits.max_object_len;
+        // a member that fails to parse is not counted, so check after it
+        let start = i.checkpoint();
+        let pair = parse_kv_pair(i, options, depth + 1)?;
+        let after = i.checkpoint();
+        i.reset(&start);
+        count_entry(i, &count, Limit::ObjectLength, max)?;
+        i.reset(&after);
+        Ok(pair)
+    };
+    let parse_kv = separated(1.., member, sep(',', options));
+    let empty = preceded(|i: &mut Input<'a>| skip_space(i, options), '}');
+    let body = alt((
+        empty.map(|_| Vec::new()),
+        terminated(parse_kv, close_object),
+    ));
+    let pairs: Vec<(_, V::Key, V)> = preceded(
+        |i: &mut Input<'a>| open(i, '{', options, depth + 1),
+        cut_err(body).context(StrContext::Label("object")),
+    )
+    .parse_next(input)?;
+
+    let mut obj = IndexMap::with_capacity(pairs.len());
+    for (start, key, value) in pairs {
+        match obj.entry(key) {
+            Entry::Vacant(e) => {
+                e.insert(value);
+            }
+            Entry::Occupied(mut e) => match options.duplicate_keys {
+                DuplicateKeys::LastWins => {
+                    e.insert(value);
+                }
+                DuplicateKeys::FirstWins => {}
+                DuplicateKeys::Error => {
+                    // point the error at the repeated key
+                    input.reset(&start);
+                    let err = DuplicateKey(e.key().as_ref().to_string());
+                    return Err(ErrMode::Cut(ContextError::from_external_error(
+                        input,
+                        ErrorKind::Verify,
+                        err,
+                    )));
+                }
+            },
+        }
+    }
+    Ok(obj)
+}
+
+/// A `"key": value` member, together with where it starts so a duplicate
+/// key can be reported at its own position.
+fn parse_kv_pair<'a, V: Value<'a>>(
+    input: &mut Input<'a>,
+    options: &ParseOptions,
+    depth: usize,
+) -> PResult<(<Input<'a> as Stream>::Checkpoint, V::Key, V)> {
+    let start = input.checkpoint();
+    let key = (|i: &mut Input<'a>| parse_key(i, options)).context(description("string key"));
+    let sep_colon = sep(':', options).context(expected(':'));
+    let value = |i: &mut Input<'a>| parse_value(i, options, depth);
+    let (k, v) = separated_pair(key, sep_colon, value).parse_next(input)?;
+    Ok((start, V::key(k), v))
+}
+
+/// A value inside `depth` containers.
+fn parse_value<'a, V: Value<'a>>(
+    input: &mut Input<'a>,
+    options: &ParseOptions,
+    depth: usize,
+) -> PResult<V> {
+    let remaining = input.len();
+    alt((
+        parse_null.map(|_| V::null()),
+        parse_bool.map(V::bool),
+        (|i: &mut Input<'a>| parse_plus(i, options)),
+        (|i: &mut Input<'a>| parse_non_finite(i, options)).map(V::double),
+        (|i: &mut Input<'a>| parse_hex(i, options)).map(|n| V::number(&n, options)),
+        parse_number.map(|n| V::number(n, options)),
+        (|i: &mut Input<'a>| parse_any_string(i, options)).map(V::string),
+        (|i: &mut Input<'a>| parse_array(i, options, depth)).map(V::array),
+        (|i: &mut Input<'a>| parse_object(i, options, depth)).map(V::object),
+    ))
+    .context(expected("null"))
+    .context(expected("true"))
+    .context(expected("false"))
+    .context(description("number"))
+    .context(description("string"))
+    .context(expected('['))
+    .context(expected('{'))
+    .context(StrContext::Label("value"))
+    .parse_next(input)
+    .map(|v: V| v.with_remaining(remaining))
+}
+
+pub fn sep_with_space<Input, Output, Error, ParseNext>(
+    mut parser: ParseNext,
+) -> impl Parser<Input, (), Error>
+where
+    Input: Stream + StreamIsPartial,
+    <Input as Stream>::Token: AsChar + Clone,
+    Error: ParserError<Input>,
+    ParseNext: Parser<Input, Output, Error>,
+{
+    trace("sep_with_space", move |input: &mut Input| {
+        let _ = multispace0.parse_next(input)?;
+        let _ = parser.parse_next(input)?;
+        let _ = multispace0.parse_next(input)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{Dialect, Limit, ParseLimits};
+
+    fn complete(input: &str) -> Input<'_> {
+        let mut input = Partial::new(input);
+        let _ = input.complete();
+        input
+    }
+
+    #[test]
+    fn test_parse_null_should_work() -> PResult<(), ContextError> {
+        let input = "null";
+        parse_null(&mut complete(input))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bool_should_work() -> PResult<(), ContextError> {
+        let input = "false";
+        let ret = parse_bool(&mut complete(input))?;
+        assert!(!ret);
+        let input = "true";
+        let ret = parse_bool(&mut complete(input))?;
+        assert!(ret);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_number_should_work() -> PResult<(), ContextError> {
+        for input in [
+            "90",
+            "-89",
+            "0",
+            "-0",
+            "1e5",
+            "-1.5E-3",
+            "18446744073709551616",
+        ] {
+            assert_eq!(parse_number(&mut complete(input))?, input);
+        }
+        assert_eq!(parse("1e5").unwrap(), JsonValue::Double(1e5));
+        assert_eq!(parse("-9").unwrap(), JsonValue::Integer(-9));
+        assert!(matches!(parse("-0").unwrap(), JsonValue::Double(v) if v.is_sign_negative()));
+        assert_eq!(
+            parse("9223372036854775808").unwrap(),
+            JsonValue::Double(9223372036854775808.0)
+        );
+        for input in [
+            "[01]",
+            "[-]",
+            "[1.]",
+            "[.5]",
+            "[1e]",
+            "[+1]",
+            "[NaN]",
+            "[-Infinity]",
+        ] {
+            assert!(parse(input).is_err(), "{}", input);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lossless_numbers_should_keep_lexeme() {
+        let options = ParseOptions {
+            lossless_numbers: true,
+            ..Default::default()
+        };
+        let input = "[9007199254740993,18446744073709551615,0.10,1e400,-0]";
+        let value = parse_with(input, &options).unwrap();
+        assert_eq!(value.to_string(), input);
+        let JsonValue::Array(values) = &value else {
+            panic!("expected array");
+        };
+        assert_eq!(values[0].as_i64(), Some(9007199254740993));
+        assert_eq!(values[1].as_u64(), Some(u64::MAX));
+        assert!(matches!(
+            parse_borrowed_with(input, &options).unwrap(),
+            borrowed::JsonValue::Array(v) if matches!(&v[2], borrowed::JsonValue::Number(n) if n.as_str() == "0.10")
+        ));
+    }
+
+    #[test]
+    fn test_parse_string_should_work() -> PResult<(), ContextError> {
+        let input = r#""a string""#;
+        let ret = parse_string(&mut complete(input))?;
+        assert_eq!(ret, "a string");
+
+        let input = r#""say \"hi\"""#;
+        let ret = parse_string(&mut complete(input))?;
+        assert_eq!(ret, "say \"hi\"");
+
+        let input = r#""\\\/\b\f\n\r\t""#;
+        let ret = parse_string(&mut complete(input))?;
+        assert_eq!(ret, "\\/\u{08}\u{0c}\n\r\t");
+
+        let input = r#""\u00e9\u4E2D \ud83d\ude00""#;
+        let ret = parse_string(&mut complete(input))?;
+        assert_eq!(ret, "é中 😀");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_string_should_fail() {
+        for input in [
+            r#""\ud83d""#,
+            r#""\ud83dabc""#,
+            r#""\ude00""#,
+            r#""\u12""#,
+            r#""\u+123""#,
+            r#""\x""#,
+            "\"tab\there\"",
+            "\"new\nline\"",
+            r#""unterminated"#,
+        ] {
+            assert!(parse_string(&mut complete(input)).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_array_should_work() -> PResult<(), ContextError> {
+        let input = r#" [ 1.0, 2.0, -3.0, 1.1e-30 ]"#;
+        let ret: Vec<JsonValue> = parse_array(&mut complete(input), &ParseOptions::default(), 0)?;
+        assert_eq!(
+            ret,
+            [
+                JsonValue::Double(1f64),
+                JsonValue::Double(2f64),
+                JsonValue::Double(-3f64),
+                JsonValue::Double(1.1e-30)
+            ]
+        );
+
+        let input = r#" [ 1, 2, -3, 1 ]"#;
+        let ret: Vec<JsonValue> = parse_array(&mut complete(input), &ParseOptions::default(), 0)?;
+        assert_eq!(
+            ret,
+            [
+                JsonValue::Integer(1),
+                JsonValue::Integer(2),
+                JsonValue::Integer(-3),
+                JsonValue::Integer(1)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_object_should_work() -> PResult<(), ContextError> {
+        let input = r#"{"a": 123 }"#;
+        let ret: IndexMap<_, JsonValue> =
+            parse_object(&mut complete(input), &ParseOptions::default(), 0)?;
+        assert_eq!(ret.len(), 1);
+        assert_eq!(ret.get("a"), Some(&JsonValue::Integer(123)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_object_should_keep_order() {
+        let input = r#"{"z": 1, "a": 2, "m": 3}"#;
+        let JsonValue::Object(obj) = parse(input).unwrap() else {
+            panic!("expected object");
+        };
+        assert_eq!(obj.keys().collect::<Vec<_>>(), ["z", "a", "m"]);
+    }
+
+    #[test]
+    fn test_parse_duplicate_keys_should_follow_policy() {
+        let input = r#"{"a": 1, "b": 2, "a": 3}"#;
+        let parse_a = |duplicate_keys| {
+            let options = ParseOptions {
+                duplicate_keys,
+                ..Default::default()
+            };
+            parse_with(input, &options).map(|v| match v {
+                JsonValue::Object(obj) => obj.get("a").cloned(),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            parse_a(DuplicateKeys::LastWins).unwrap(),
+            Some(JsonValue::Integer(3))
+        );
+        assert_eq!(
+            parse_a(DuplicateKeys::FirstWins).unwrap(),
+            Some(JsonValue::Integer(1))
+        );
+        match parse_a(DuplicateKeys::Error) {
+            Err(JsonError::DuplicateKey { key, offset }) => {
+                assert_eq!(key, "a");
+                assert_eq!(offset, 17);
+            }
+            ret => panic!("expected duplicate key error, got {:?}", ret),
+        }
+    }
+
+    #[test]
+    fn test_parse_json_should_work() {
+        let input = r#"{"name": "John Doe", "marks": [90, -80, 85]}"#;
+        let ret = parse(input).unwrap();
+        let JsonValue::Object(obj) = ret else {
+            panic!("expected object");
+        };
+        assert_eq!(obj.get("name"), Some(&JsonValue::String("John Doe".into())));
+        assert!(parse("{").is_err());
+    }
+
+    #[test]
+    fn test_parse_dialect_should_enable_extensions() {
+        let input = r#"// settings
+        {
+            name: 'a \'b\'', /* inline */ "list": [0x1F, -0Xff, Infinity, -Infinity, +1.5, +0x10, +Infinity, -NaN,],
+            $id_2: +NaN, // trailing
+        }"#;
+        assert!(parse(input).is_err());
+        let options = ParseOptions {
+            dialect: Dialect::JSON5,
+            ..Default::default()
+        };
+        let JsonValue::Object(obj) = parse_with(input, &options).unwrap() else {
+            panic!("expected object");
+        };
+        assert_eq!(obj["name"], JsonValue::String("a 'b'".into()));
+        assert_eq!(
+            obj["list"].to_string(),
+            JsonValue::Array(vec![
+                JsonValue::Integer(31),
+                JsonValue::Integer(-255),
+                JsonValue::Double(f64::INFINITY),
+                JsonValue::Double(f64::NEG_INFINITY),
+                JsonValue::Double(1.5),
+                JsonValue::Integer(16),
+                JsonValue::Double(f64::INFINITY),
+                JsonValue::Double(f64::NAN),
+            ])
+            .to_string()
+        );
+        assert!(matches!(obj["$id_2"], JsonValue::Double(v) if v.is_nan()));
+        for input in ["[+-1]", "[+ 1]", "[++1]", "[-+1]", "[+null]"] {
+            assert!(parse_with(input, &options).is_err(), "{}", input);
+        }
+        let lossless = ParseOptions {
+            lossless_numbers: true,
+            ..options
+        };
+        assert_eq!(parse_with("+12", &lossless).unwrap().to_string(), "12");
+
+        // each extension only with its own flag
+        let jsonc = ParseOptions {
+            dialect: Dialect::JSONC,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_with("/* a */ [1, /* b */ 2, // c\n ]", &jsonc).unwrap(),
+            parse("[1, 2]").unwrap()
+        );
+        for input in [
+            "['a']",
+            "{a: 1}",
+            "[0x1]",
+            "[NaN]",
+            "[+1]",
+            "[\"\\'\"]",
+            "[1,,]",
+            "[,]",
+            "[/* open",
+        ] {
+            assert!(parse_with(input, &jsonc).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_limits_should_be_enforced() {
+        let limit = |input: &str, limits| match parse_with(
+            input,
+            &ParseOptions {
+                limits,
+                ..Default::default()
+            },
+        ) {
+            Err(JsonError::LimitExceeded { limit, max, offset }) => (limit, max, offset),
+            ret => panic!("expected limit error, got {:?}", ret),
+        };
+
+        // fails at the first bracket too deep instead of overflowing the stack
+        let deep = "[".repeat(100_000);
+        let limits = ParseLimits::default();
+        assert_eq!(limit(&deep, limits), (Limit::Depth, 64, 64));
+        let nested = format!("{}{}", "[".repeat(64), "]".repeat(64));
+        assert!(parse(&nested).is_ok());
+
+        let limits = ParseLimits {
+            max_depth: 2,
+            max_string_len: 3,
+            max_array_len: 2,
+            max_object_len: 1,
+            max_input_len: 32,
+        };
+        assert_eq!(limit("[{\"a\": [1]}]", limits), (Limit::Depth, 2, 7));
+        assert_eq!(
+            limit("[\"abc\", \"\\u00e9\\u00e9\"]", limits),
+            (Limit::StringLength, 3, 8)
+        );
+        assert_eq!(limit("{\"abcd\": 1}", limits), (Limit::StringLength, 3, 1));
+        assert_eq!(limit("[1, 2, 3]", limits), (Limit::ArrayLength, 2, 7));
+        assert_eq!(
+            limit("{\"a\": 1, \"b\": 2}", limits),
+            (Limit::ObjectLength, 1, 9)
+        );
+        assert_eq!(
+            limit(&format!("[{}]", " ".repeat(31)), limits),
+            (Limit::InputLength, 32, 32)
+        );
+
+        let options = ParseOptions {
+            dialect: Dialect::JSON5,
+            limits,
+            ..Default::default()
+        };
+        assert!(parse_with("[1, 2,]", &options).is_ok());
+        assert!(parse_with("{abc: 'abc',}", &options).is_ok());
+        assert!(matches!(
+            parse_with("{abcd: 1}", &options),
+            Err(JsonError::LimitExceeded { offset: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_error_should_report_position() {
+        let err = |input| match parse(input) {
+            Err(JsonError::Parse(e)) => e,
+            ret => panic!("expected parse error, got {:?}", ret),
+        };
+
+        let e = err("{\n  \"a\": [1 2]\n}");
+        assert_eq!((e.offset, e.line, e.column), (12, 2, 11));
+        assert_eq!(e.label.as_deref(), Some("array"));
+        assert_eq!(e.expected, ["`,`", "`]`"]);
+        assert_eq!(e.found, Some('2'));
+
+        let e = err(r#"{"a" 1}"#);
+        assert_eq!((e.line, e.column), (1, 6));
+        assert_eq!(e.expected, ["`:`"]);
+
+        let e = err(r#"["x\q"]"#);
+        assert_eq!((e.column, e.label.as_deref()), (5, Some("string")));
+
+        let e = err("[1, ]");
+        assert_eq!(e.column, 5);
+        assert!(e.expected.contains(&"`null`".to_string()));
+        assert!(e.to_string().ends_with("1 | [1, ]\n  |     ^"));
+
+        let e = err("{\"a\": 1} x");
+        assert_eq!(e.offset, 9);
+        assert_eq!(e.expected, ["end of input"]);
+    }
+
+    #[test]
+    fn test_parse_document_should_accept_any_value() {
+        for (input, expected) in [
+            ("[]", JsonValue::Array(vec![])),
+            (" { } ", JsonValue::Object(Default::default())),
+            ("\"x\"", JsonValue::String("x".into())),
+            ("\n42\r\n", JsonValue::Integer(42)),
+            ("null", JsonValue::Null),
+            (
+                "[{}, [], {\"a\": {}}]",
+                parse("[{}, [], {\"a\": {}}]").unwrap(),
+            ),
+        ] {
+            assert_eq!(parse(input).unwrap(), expected, "{:?}", input);
+        }
+        for input in ["", " ", "[] []", "1 2", "{},", "nullx", "{,}", "[,]"] {
+            assert!(parse(input).is_err(), "{:?}", input);
+        }
+
+        let jsonc = ParseOptions {
+            dialect: Dialect::JSONC,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_with("// head\n1 /* tail */", &jsonc).unwrap(),
+            JsonValue::Integer(1)
+        );
+        assert!(parse_with("{,}", &jsonc).is_err());
+    }
+}
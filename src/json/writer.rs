@@ -0,0 +1,348 @@
+use std::{fmt, io};
+
+use super::{JsonError, JsonValue};
+
+/// Output layout for [`write`], [`write_io`] and [`to_string`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Break arrays and objects over several lines.
+    pub pretty: bool,
+    /// Spaces per nesting level in pretty mode.
+    pub indent: usize,
+    /// Emit object members ordered by key instead of in storage order.
+    pub sort_keys: bool,
+    /// Write non-finite doubles as the JSON5 tokens `NaN`, `Infinity` and
+    /// `-Infinity` instead of failing with [`JsonError::NonFiniteNumber`].
+    pub non_finite_numbers: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self::compact()
+    }
+}
+
+impl WriteOptions {
+    pub fn compact() -> Self {
+        Self {
+            pretty: false,
+            indent: 2,
+            sort_keys: false,
+            non_finite_numbers: false,
+        }
+    }
+
+    pub fn pretty() -> Self {
+        Self {
+            pretty: true,
+            ..Self::compact()
+        }
+    }
+}
+
+/// Write `value` as JSON into any [`fmt::Write`] sink.
+pub fn write<W: fmt::Write>(
+    w: &mut W,
+    value: &JsonValue,
+    options: &WriteOptions,
+) -> Result<(), JsonError> {
+    Writer { w, options }.write_value(value, 0)
+}
+
+/// Write `value` as JSON into any [`io::Write`] sink.
+pub fn write_io<W: io::Write>(
+    w: W,
+    value: &JsonValue,
+    options: &WriteOptions,
+) -> Result<(), JsonError> {
+    let mut adapter = IoAdapter {
+        inner: w,
+        error: None,
+    };
+    match write(&mut adapter, value, options) {
+        Err(JsonError::Fmt(_)) => Err(adapter
+            .error
+            .map(JsonError::Io)
+            .unwrap_or(JsonError::Fmt(fmt::Error))),
+        ret => ret,
+    }
+}
+
+pub fn to_string(value: &JsonValue, options: &WriteOptions) -> Result<String, JsonError> {
+    let mut s = String::new();
+    write(&mut s, value, options)?;
+    Ok(s)
+}
+
+impl JsonValue {
+    pub fn to_string_pretty(&self) -> Result<String, JsonError> {
+        to_string(self, &WriteOptions::pretty())
+    }
+}
+
+/// Compact JSON, or pretty JSON with the alternate flag (`{:#}`). Non-finite
+/// numbers, which only the JSON5 dialect parses, are written as JSON5 tokens;
+/// use [`to_string`] to reject them instead.
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = WriteOptions {
+            non_finite_numbers: true,
+            ..if f.alternate() {
+                WriteOptions::pretty()
+            } else {
+                WriteOptions::compact()
+            }
+        };
+        write(f, self, &options).map_err(|_| fmt::Error)
+    }
+}
+
+struct Writer<'a, W> {
+    w: &'a mut W,
+    options: &'a WriteOptions,
+}
+
+impl<W: fmt::Write> Writer<'_, W> {
+    fn write_value(&mut self, value: &JsonValue, depth: usize) -> Result<(), JsonError> {
+        match value {
+            JsonValue::Null => self.w.write_str("null")?,
+            JsonValue::Bool(v) => self.w.write_str(if *v { "true" } else { "false" })?,
+            JsonValue::Integer(v) => write!(self.w, "{}", v)?,
+            JsonValue::Double(v) => self.write_double(*v)?,
+            JsonValue::Number(v) => self.w.write_str(v.as_str())?,
+            JsonValue::String(v) => write_string(self.w, v)?,
+            JsonValue::Array(values) => {
+                self.write_container('[', ']', values.is_empty(), depth, |this| {
+                    for (i, v) in values.iter().enumerate() {
+                        this.write_separator(i, depth + 1)?;
+                        this.write_value(v, depth + 1)?;
+                    }
+                    Ok(())
+                })?
+            }
+            JsonValue::Object(obj) => {
+                let mut members: Vec<_> = obj.iter().collect();
+                if self.options.sort_keys {
+                    members.sort_by(|a, b| a.0.cmp(b.0));
+                }
+                self.write_container('{', '}', members.is_empty(), depth, |this| {
+                    for (i, (k, v)) in members.into_iter().enumerate() {
+                        this.write_separator(i, depth + 1)?;
+                        write_string(this.w, k)?;
+                        this.w
+                            .write_str(if this.options.pretty { ": " } else { ":" })?;
+                        this.write_value(v, depth + 1)?;
+                    }
+                    Ok(())
+                })?
+            }
+        }
+        Ok(())
+    }
+
+    fn write_container(
+        &mut self,
+        open: char,
+        close: char,
+        empty: bool,
+        depth: usize,
+        f: impl FnOnce(&mut Self) -> Result<(), JsonError>,
+    ) -> Result<(), JsonError> {
+        self.w.write_char(open)?;
+        if empty {
+            self.w.write_char(close)?;
+            return Ok(());
+        }
+        f(self)?;
+        if self.options.pretty {
+            self.write_newline(depth)?;
+        }
+        self.w.write_char(close)?;
+        Ok(())
+    }
+
+    fn write_separator(&mut self, index: usize, depth: usize) -> Result<(), JsonError> {
+        if index > 0 {
+            self.w.write_char(',')?;
+        }
+        if self.options.pretty {
+            self.write_newline(depth)?;
+        }
+        Ok(())
+    }
+
+    fn write_newline(&mut self, depth: usize) -> Result<(), JsonError> {
+        self.w.write_char('\n')?;
+        for _ in 0..depth * self.options.indent {
+            self.w.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn write_double(&mut self, v: f64) -> Result<(), JsonError> {
+        if !v.is_finite() {
+            if !self.options.non_finite_numbers {
+                return Err(JsonError::NonFiniteNumber(v));
+            }
+            let token = match v {
+                v if v.is_nan() => "NaN",
+                v if v > 0.0 => "Infinity",
+                _ => "-Infinity",
+            };
+            self.w.write_str(token)?;
+            return Ok(());
+        }
+        // `Debug` is the shortest round-trip form and always keeps a fraction
+        // or an exponent, so the value reads back as a double, not an integer
+        write!(self.w, "{:?}", v)?;
+        Ok(())
+    }
+}
+
+fn write_string<W: fmt::Write + ?Sized>(w: &mut W, s: &str) -> fmt::Result {
+    w.write_char('"')?;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{08}' => "\\b",
+            '\u{0c}' => "\\f",
+            '\0'..='\u{1f}' => "",
+            _ => continue,
+        };
+        w.write_str(&s[start..i])?;
+        if escaped.is_empty() {
+            write!(w, "\\u{:04x}", c as u32)?;
+        } else {
+            w.write_str(escaped)?;
+        }
+        start = i + c.len_utf8();
+    }
+    w.write_str(&s[start..])?;
+    w.write_char('"')
+}
+
+struct IoAdapter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse, parse_with, Dialect, JsonObject, ParseOptions};
+
+    fn sample() -> JsonValue {
+        let mut address = JsonObject::new();
+        address.insert("zip".to_string(), JsonValue::Integer(10001));
+        address.insert("city".to_string(), JsonValue::String("New York".into()));
+        let mut obj = JsonObject::new();
+        obj.insert("name".to_string(), JsonValue::String("J \"D\"\n".into()));
+        obj.insert(
+            "marks".to_string(),
+            JsonValue::Array(vec![JsonValue::Double(90.0), JsonValue::Integer(-80)]),
+        );
+        obj.insert("address".to_string(), JsonValue::Object(address));
+        obj.insert("empty".to_string(), JsonValue::Array(vec![]));
+        JsonValue::Object(obj)
+    }
+
+    #[test]
+    fn write_compact_should_work() {
+        let options = WriteOptions {
+            sort_keys: true,
+            ..WriteOptions::compact()
+        };
+        let s = to_string(&sample(), &options).unwrap();
+        assert_eq!(
+            s,
+            r#"{"address":{"city":"New York","zip":10001},"empty":[],"marks":[90.0,-80],"name":"J \"D\"\n"}"#
+        );
+    }
+
+    #[test]
+    fn write_pretty_should_work() {
+        let options = WriteOptions {
+            indent: 4,
+            sort_keys: true,
+            ..WriteOptions::pretty()
+        };
+        let s = to_string(&sample(), &options).unwrap();
+        let expected = r#"{
+    "address": {
+        "city": "New York",
+        "zip": 10001
+    },
+    "empty": [],
+    "marks": [
+        90.0,
+        -80
+    ],
+    "name": "J \"D\"\n"
+}"#;
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn write_string_should_escape() {
+        let v = JsonValue::String("\\/\u{08}\u{0c}\r\t\u{01}é😀".into());
+        assert_eq!(v.to_string(), r#""\\/\b\f\r\t\u0001é😀""#);
+    }
+
+    #[test]
+    fn write_double_should_round_trip() {
+        for v in [1.0, -0.5, 1.1e-30, 1e16, 123456.789, f64::MAX] {
+            let s = JsonValue::Double(v).to_string();
+            assert_eq!(parse(&s).unwrap(), JsonValue::Double(v), "{}", s);
+        }
+        assert!(matches!(
+            to_string(&JsonValue::Double(f64::NAN), &WriteOptions::default()),
+            Err(JsonError::NonFiniteNumber(_))
+        ));
+    }
+
+    #[test]
+    fn display_should_write_non_finite_as_json5() {
+        let v = JsonValue::Array(vec![
+            JsonValue::Double(f64::NAN),
+            JsonValue::Double(f64::INFINITY),
+            JsonValue::Double(f64::NEG_INFINITY),
+        ]);
+        assert_eq!(v.to_string(), "[NaN,Infinity,-Infinity]");
+        assert_eq!(format!("{:#}", v), "[\n  NaN,\n  Infinity,\n  -Infinity\n]");
+
+        let options = ParseOptions {
+            dialect: Dialect::JSON5,
+            ..Default::default()
+        };
+        let back = parse_with(&v.to_string(), &options).unwrap();
+        assert_eq!(back.to_string(), v.to_string());
+    }
+
+    #[test]
+    fn write_should_round_trip() {
+        let v = sample();
+        assert_eq!(parse(&v.to_string()).unwrap(), v);
+        assert_eq!(parse(&format!("{:#}", v)).unwrap(), v);
+    }
+
+    #[test]
+    fn write_io_should_work() {
+        let mut buf = Vec::new();
+        write_io(&mut buf, &JsonValue::Array(vec![]), &WriteOptions::pretty()).unwrap();
+        assert_eq!(buf, b"[]");
+    }
+}
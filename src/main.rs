@@ -1,3 +1,486 @@
-fn main() {
-    println!("Hello, world!");
+//! `grammar`: format, validate and query JSON documents and parse nginx access
+//! logs from the command line. Input comes from a file, or from stdin when the
+//! file is omitted or `-`.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    fs,
+    io::{self, Read, Write},
+    net::IpAddr,
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use grammar::{
+    json::{self, Dialect, Filter, JsonValue, ParseOptions, Schema, WriteOptions},
+    nginx::{self, NginxError, NginxLog},
+};
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(name = "grammar", version, about = "JSON and nginx log tools")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Work with JSON documents.
+    Json(JsonArgs),
+    /// Work with nginx access logs in the combined format.
+    Nginx(NginxArgs),
+}
+
+#[derive(Debug, Args)]
+struct JsonArgs {
+    #[arg(long, value_enum, default_value_t = JsonBackend::Winnow, global = true)]
+    backend: JsonBackend,
+    /// Extensions to accept beyond strict JSON.
+    #[arg(long, value_enum, default_value_t = JsonDialect::Strict, global = true)]
+    dialect: JsonDialect,
+    /// Output format, pretty JSON by default.
+    #[arg(short, long, value_enum, global = true)]
+    output: Option<Output>,
+    #[command(subcommand)]
+    command: JsonCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum JsonCommand {
+    /// Reformat a document.
+    Fmt {
+        file: Option<PathBuf>,
+        #[arg(long)]
+        sort_keys: bool,
+    },
+    /// Check that documents parse, and optionally match a JSON Schema.
+    Validate {
+        files: Vec<PathBuf>,
+        #[arg(long)]
+        schema: Option<PathBuf>,
+    },
+    /// Run a jq-style filter, or a JSONPath query with `--jsonpath`, and print
+    /// each result.
+    Query {
+        query: String,
+        file: Option<PathBuf>,
+        #[arg(long)]
+        jsonpath: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+struct NginxArgs {
+    #[arg(long, value_enum, default_value_t = NginxBackend::Winnow, global = true)]
+    backend: NginxBackend,
+    /// Output format, compact JSON lines for `parse` and a table for `stats`
+    /// by default.
+    #[arg(short, long, value_enum, global = true)]
+    output: Option<Output>,
+    #[command(subcommand)]
+    command: NginxCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum NginxCommand {
+    /// Parse every line into a record.
+    Parse { file: Option<PathBuf> },
+    /// Summarize requests by status, method and URL.
+    Stats {
+        file: Option<PathBuf>,
+        /// How many of the most requested URLs to list.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum JsonBackend {
+    Pest,
+    Winnow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum JsonDialect {
+    Strict,
+    Jsonc,
+    Json5,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum NginxBackend {
+    Regex,
+    Winnow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    Pretty,
+    Compact,
+    Table,
+}
+
+/// The text of one input, with the name to report it under.
+struct Input {
+    name: String,
+    text: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Json(args) => run_json(args),
+        Command::Nginx(args) => run_nginx(args),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        // the reader went away, e.g. `grammar nginx parse | head`
+        Err(e) if is_broken_pipe(&e) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs a `json` subcommand, returning whether every input was valid.
+fn run_json(args: JsonArgs) -> Result<bool> {
+    let backend = match args.backend {
+        JsonBackend::Pest => {
+            // diagnostics as precise as winnow's, for slower pest parsing in
+            // the rest of this short-lived process
+            json::pest::enable_error_detail();
+            "pest"
+        }
+        JsonBackend::Winnow => "winnow",
+    };
+    let backend = json::backend(backend).expect("every backend is registered");
+    let options = ParseOptions {
+        dialect: match args.dialect {
+            JsonDialect::Strict => Dialect::STRICT,
+            JsonDialect::Jsonc => Dialect::JSONC,
+            JsonDialect::Json5 => Dialect::JSON5,
+        },
+        ..Default::default()
+    };
+    let parse = |input: &Input| {
+        backend
+            .parse_with(&input.text, &options)
+            .with_context(|| input.name.clone())
+    };
+    let output = args.output.unwrap_or(Output::Pretty);
+    let mut out = io::stdout().lock();
+
+    match args.command {
+        JsonCommand::Fmt { file, sort_keys } => {
+            let value = parse(&read_input(file)?)?;
+            emit(&mut out, vec![value], output, sort_keys)?;
+        }
+        JsonCommand::Query {
+            query,
+            file,
+            jsonpath,
+        } => {
+            let value = parse(&read_input(file)?)?;
+            let results = if jsonpath {
+                let nodes = value.query(&query)?;
+                nodes.into_iter().map(|node| node.value.clone()).collect()
+            } else {
+                Filter::parse(&query)?.run(&value)?
+            };
+            emit(&mut out, results, output, false)?;
+        }
+        JsonCommand::Validate { files, schema } => {
+            let schema = match schema {
+                Some(path) => Some(Schema::new(parse(&read_input(Some(path))?)?)?),
+                None => None,
+            };
+            let files = if files.is_empty() {
+                vec![None]
+            } else {
+                files.into_iter().map(Some).collect()
+            };
+            let mut valid = true;
+            for file in files {
+                let input = read_input(file)?;
+                let errors = match backend.parse_with(&input.text, &options) {
+                    Ok(value) => match schema.as_ref().map(|schema| schema.validate(&value)) {
+                        Some(Err(errors)) => errors.iter().map(ToString::to_string).collect(),
+                        _ => Vec::new(),
+                    },
+                    Err(e) => vec![e.to_string()],
+                };
+                if errors.is_empty() {
+                    writeln!(out, "{}: valid", input.name)?;
+                }
+                for error in &errors {
+                    report(&input.name, error);
+                }
+                valid &= errors.is_empty();
+            }
+            return Ok(valid);
+        }
+    }
+    Ok(true)
+}
+
+/// Runs an `nginx` subcommand, returning whether every line parsed. Lines that
+/// fail are reported and skipped.
+fn run_nginx(args: NginxArgs) -> Result<bool> {
+    let parse: fn(&str) -> Result<NginxLog, NginxError> = match args.backend {
+        NginxBackend::Regex => nginx::regex::parse,
+        NginxBackend::Winnow => nginx::winnow::parse,
+    };
+    let (file, default_output) = match &args.command {
+        NginxCommand::Parse { file } => (file.clone(), Output::Compact),
+        NginxCommand::Stats { file, .. } => (file.clone(), Output::Table),
+    };
+    let output = args.output.unwrap_or(default_output);
+    let input = read_input(file)?;
+    let mut out = io::stdout().lock();
+
+    let mut logs = Vec::new();
+    let mut failed = 0;
+    for (i, line) in input.text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse(line) {
+            Ok(log) => logs.push(log),
+            Err(e) => {
+                report(&format!("{}:{}", input.name, i + 1), e);
+                failed += 1;
+            }
+        }
+    }
+
+    match args.command {
+        NginxCommand::Parse { .. } => {
+            let values = logs.iter().map(json::to_value).collect::<Result<_, _>>()?;
+            emit(&mut out, values, output, false)?;
+        }
+        NginxCommand::Stats { top, .. } => {
+            let stats = Stats::new(&logs, failed, top);
+            emit(&mut out, vec![json::to_value(&stats)?], output, false)?;
+        }
+    }
+    Ok(failed == 0)
+}
+
+fn read_input(file: Option<PathBuf>) -> Result<Input> {
+    match file {
+        Some(path) if path.as_os_str() != "-" => {
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("cannot read {}", path.display()))?;
+            Ok(Input {
+                name: path.display().to_string(),
+                text,
+            })
+        }
+        _ => {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .context("cannot read stdin")?;
+            Ok(Input {
+                name: "<stdin>".to_string(),
+                text,
+            })
+        }
+    }
+}
+
+fn report(source: &str, error: impl Display) {
+    eprintln!("error: {}: {}", source, error);
+}
+
+fn is_broken_pipe(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+}
+
+/// Prints each value on its own, or all of them as one table.
+fn emit(
+    out: &mut impl Write,
+    values: Vec<JsonValue>,
+    output: Output,
+    sort_keys: bool,
+) -> Result<()> {
+    let options = match output {
+        Output::Pretty => WriteOptions::pretty(),
+        Output::Compact => WriteOptions::compact(),
+        Output::Table => {
+            let value = match <[JsonValue; 1]>::try_from(values) {
+                Ok([value]) => value,
+                Err(values) => JsonValue::Array(values),
+            };
+            write!(out, "{}", table(&value)?)?;
+            return Ok(());
+        }
+    };
+    let options = WriteOptions {
+        sort_keys,
+        ..options
+    };
+    for value in &values {
+        writeln!(out, "{}", json::to_string(value, &options)?)?;
+    }
+    Ok(())
+}
+
+/// Lays out an array of objects with one column per key, an object as
+/// key/value rows and anything else as a single column. The arrays and objects
+/// inside an object get their own table below it; deeper ones are written as
+/// compact JSON.
+fn table(value: &JsonValue) -> Result<String> {
+    match value {
+        JsonValue::Array(items)
+            if !items.is_empty() && items.iter().all(|v| matches!(v, JsonValue::Object(_))) =>
+        {
+            let mut columns: Vec<&str> = Vec::new();
+            for item in items {
+                if let JsonValue::Object(object) = item {
+                    for key in object.keys() {
+                        if !columns.contains(&key.as_str()) {
+                            columns.push(key);
+                        }
+                    }
+                }
+            }
+            let mut rows = Vec::new();
+            for item in items {
+                let mut row = Vec::new();
+                for column in &columns {
+                    row.push(match item {
+                        JsonValue::Object(object) => object.get(*column).map(cell).transpose()?,
+                        _ => None,
+                    });
+                }
+                rows.push(row.into_iter().map(Option::unwrap_or_default).collect());
+            }
+            Ok(grid(&columns, &rows))
+        }
+        JsonValue::Array(items) => {
+            let rows = items
+                .iter()
+                .map(|item| Ok(vec![cell(item)?]))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(grid(&["value"], &rows))
+        }
+        JsonValue::Object(object) => {
+            let is_container =
+                |v: &JsonValue| matches!(v, JsonValue::Array(_) | JsonValue::Object(_));
+            let mut rows = Vec::new();
+            for (key, value) in object.iter().filter(|(_, v)| !is_container(v)) {
+                rows.push(vec![key.clone(), cell(value)?]);
+            }
+            let mut s = String::new();
+            if !rows.is_empty() || object.values().all(|v| !is_container(v)) {
+                s.push_str(&grid(&["key", "value"], &rows));
+            }
+            for (key, value) in object.iter().filter(|(_, v)| is_container(v)) {
+                if !s.is_empty() {
+                    s.push('\n');
+                }
+                s.push_str(&format!("{}:\n{}", key, table(value)?));
+            }
+            Ok(s)
+        }
+        scalar => Ok(format!("{}\n", cell(scalar)?)),
+    }
+}
+
+/// Strings as they are, anything else as compact JSON.
+fn cell(value: &JsonValue) -> Result<String> {
+    match value {
+        JsonValue::String(s) => Ok(s.clone()),
+        value => Ok(json::to_string(value, &WriteOptions::compact())?),
+    }
+}
+
+fn grid(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &mut dyn Iterator<Item = &str>| {
+        let padded: Vec<String> = cells
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+    let mut s = line(&mut headers.iter().copied());
+    let rules: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    s.push_str(&line(&mut rules.iter().map(String::as_str)));
+    for row in rows {
+        s.push_str(&line(&mut row.iter().map(String::as_str)));
+    }
+    s
+}
+
+/// The summary printed by `grammar nginx stats`.
+#[derive(Debug, Serialize)]
+struct Stats {
+    requests: usize,
+    failed: usize,
+    body_bytes: u64,
+    clients: usize,
+    first: Option<DateTime<Utc>>,
+    last: Option<DateTime<Utc>>,
+    status: BTreeMap<u16, usize>,
+    methods: BTreeMap<String, usize>,
+    top_urls: Vec<UrlCount>,
+}
+
+#[derive(Debug, Serialize)]
+struct UrlCount {
+    url: String,
+    requests: usize,
+}
+
+impl Stats {
+    fn new(logs: &[NginxLog], failed: usize, top: usize) -> Self {
+        let mut status = BTreeMap::new();
+        let mut methods = BTreeMap::new();
+        let mut urls: HashMap<&str, usize> = HashMap::new();
+        for log in logs {
+            *status.entry(log.status).or_default() += 1;
+            *methods.entry(log.method.to_string()).or_default() += 1;
+            *urls.entry(&log.url).or_default() += 1;
+        }
+        let mut top_urls: Vec<UrlCount> = urls
+            .into_iter()
+            .map(|(url, requests)| UrlCount {
+                url: url.to_string(),
+                requests,
+            })
+            .collect();
+        top_urls.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.url.cmp(&b.url)));
+        top_urls.truncate(top);
+
+        Self {
+            requests: logs.len(),
+            failed,
+            body_bytes: logs.iter().map(|log| log.body_bytes).sum(),
+            clients: logs
+                .iter()
+                .map(|log| log.addr)
+                .collect::<HashSet<IpAddr>>()
+                .len(),
+            first: logs.iter().map(|log| log.datetime).min(),
+            last: logs.iter().map(|log| log.datetime).max(),
+            status,
+            methods,
+            top_urls,
+        }
+    }
 }
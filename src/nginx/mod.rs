@@ -0,0 +1,137 @@
+pub mod regex;
+pub mod winnow;
+
+use std::{fmt, net::IpAddr, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+    Head,
+    Options,
+    Connect,
+    Trace,
+    Patch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HttpProto {
+    #[serde(rename = "HTTP/1.0")]
+    HTTP1_0,
+    #[serde(rename = "HTTP/1.1")]
+    HTTP1_1,
+    #[serde(rename = "HTTP/2.0")]
+    HTTP2_0,
+    #[serde(rename = "HTTP/3.0")]
+    HTTP3_0,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NginxLog {
+    pub addr: IpAddr,
+    pub datetime: DateTime<Utc>,
+    pub method: HttpMethod,
+    pub url: String,
+    pub protocol: HttpProto,
+    pub status: u16,
+    pub body_bytes: u64,
+    pub referer: String,
+    pub user_agent: String,
+}
+
+#[derive(Debug, Error)]
+pub enum NginxError {
+    #[error("Failed to parse nginx log: {0}")]
+    Parse(String),
+    /// The line does not have the combined log format at `column`, counted
+    /// in characters from 1.
+    #[error("Failed to parse nginx log: expected {expected} at column {column}")]
+    Syntax { column: usize, expected: String },
+    #[error("Invalid HTTP method: {0}")]
+    InvalidMethod(String),
+    #[error("Invalid HTTP protocol: {0}")]
+    InvalidProtocol(String),
+    #[error("Invalid datetime: {0}")]
+    InvalidDatetime(#[from] chrono::ParseError),
+}
+
+/// Parse a single nginx access log line with the default (winnow) backend.
+pub fn parse(input: &str) -> Result<NginxLog, NginxError> {
+    winnow::parse(input)
+}
+
+const DATETIME_FORMAT: &str = "%d/%b/%Y:%H:%M:%S %z";
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>, NginxError> {
+    let dt = DateTime::parse_from_str(s, DATETIME_FORMAT)?;
+    Ok(dt.with_timezone(&Utc))
+}
+
+impl FromStr for HttpProto {
+    type Err = NginxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HTTP/1.0" => Ok(HttpProto::HTTP1_0),
+            "HTTP/1.1" => Ok(HttpProto::HTTP1_1),
+            "HTTP/2.0" => Ok(HttpProto::HTTP2_0),
+            "HTTP/3.0" => Ok(HttpProto::HTTP3_0),
+            _ => Err(NginxError::InvalidProtocol(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for HttpProto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            HttpProto::HTTP1_0 => "HTTP/1.0",
+            HttpProto::HTTP1_1 => "HTTP/1.1",
+            HttpProto::HTTP2_0 => "HTTP/2.0",
+            HttpProto::HTTP3_0 => "HTTP/3.0",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for HttpMethod {
+    type Err = NginxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "GET" => Ok(HttpMethod::Get),
+            "POST" => Ok(HttpMethod::Post),
+            "PUT" => Ok(HttpMethod::Put),
+            "DELETE" => Ok(HttpMethod::Delete),
+            "HEAD" => Ok(HttpMethod::Head),
+            "OPTIONS" => Ok(HttpMethod::Options),
+            "CONNECT" => Ok(HttpMethod::Connect),
+            "TRACE" => Ok(HttpMethod::Trace),
+            "PATCH" => Ok(HttpMethod::Patch),
+            _ => Err(NginxError::InvalidMethod(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Connect => "CONNECT",
+            HttpMethod::Trace => "TRACE",
+            HttpMethod::Patch => "PATCH",
+        };
+        f.write_str(s)
+    }
+}
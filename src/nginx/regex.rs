@@ -0,0 +1,76 @@
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use super::{NginxError, NginxLog};
+
+fn nginx_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"^(?<ip>\S+)\s+\S+\s+\S+\s+\[(?<date>[^\]]+)\]\s+"(?<method>\S+)\s+(?<url>\S+)\s+(?<proto>[^"]+)"\s+(?<status>\d+)\s+(?<bytes>\d+)\s+"(?<referer>[^"]+)"\s+"(?<ua>[^"]+)"$"#,
+        )
+        .expect("nginx log regex should be valid")
+    })
+}
+
+pub fn parse(s: &str) -> Result<NginxLog, NginxError> {
+    let cap = nginx_regex()
+        .captures(s)
+        .ok_or_else(|| NginxError::Parse(s.to_string()))?;
+    let addr = field(&cap, "ip")?
+        .parse()
+        .map_err(|_| NginxError::Parse(format!("invalid ip address in {}", s)))?;
+    let datetime = super::parse_datetime(field(&cap, "date")?)?;
+    let method = field(&cap, "method")?.parse()?;
+    let url = field(&cap, "url")?.to_string();
+    let protocol = field(&cap, "proto")?.parse()?;
+    let status = field(&cap, "status")?
+        .parse()
+        .map_err(|_| NginxError::Parse(format!("invalid status in {}", s)))?;
+    let body_bytes = field(&cap, "bytes")?
+        .parse()
+        .map_err(|_| NginxError::Parse(format!("invalid body bytes in {}", s)))?;
+    let referer = field(&cap, "referer")?.to_string();
+    let user_agent = field(&cap, "ua")?.to_string();
+    Ok(NginxLog {
+        addr,
+        datetime,
+        method,
+        url,
+        protocol,
+        status,
+        body_bytes,
+        referer,
+        user_agent,
+    })
+}
+
+fn field<'a>(cap: &Captures<'a>, name: &str) -> Result<&'a str, NginxError> {
+    cap.name(name)
+        .map(|m| m.as_str())
+        .ok_or_else(|| NginxError::Parse(format!("missing field {}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nginx::{HttpMethod, HttpProto};
+    use anyhow::Result;
+
+    #[test]
+    fn regex_parse_nginx_log_should_work() -> Result<()> {
+        let s = r#"93.180.71.3 - - [17/May/2015:08:05:32 +0000] "GET /downloads/product_1 HTTP/1.1" 304 0 "-" "Debian APT-HTTP/1.3 (0.8.16~exp12ubuntu10.21)""#;
+        let log = parse(s)?;
+        assert_eq!(log, crate::nginx::winnow::parse(s)?);
+        assert_eq!(log.method, HttpMethod::Get);
+        assert_eq!(log.protocol, HttpProto::HTTP1_1);
+        Ok(())
+    }
+
+    #[test]
+    fn regex_parse_invalid_method_should_fail() {
+        let s = r#"93.180.71.3 - - [17/May/2015:08:05:32 +0000] "FETCH /downloads/product_1 HTTP/1.1" 304 0 "-" "curl""#;
+        assert!(matches!(parse(s), Err(NginxError::InvalidMethod(_))));
+    }
+}
@@ -0,0 +1,260 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use winnow::{
+    ascii::digit1,
+    combinator::{alt, delimited, eof},
+    error::{ContextError, ErrMode, StrContext, StrContextValue},
+    token::{take_till, take_until, take_while},
+    PResult, Parser,
+};
+
+use super::{HttpMethod, HttpProto, NginxError, NginxLog};
+
+/// Parse one line, accepting exactly what [`super::regex::parse`] accepts:
+/// fields separated by any whitespace and nothing after the user agent.
+pub fn parse(s: &str) -> Result<NginxLog, NginxError> {
+    let mut rest = s;
+    parse_nginx_log(&mut rest).map_err(|e| to_nginx_error(s, rest, e))
+}
+
+/// Where parsing stopped and the first field or token expected there.
+fn to_nginx_error(input: &str, rest: &str, e: ErrMode<ContextError>) -> NginxError {
+    let offset = input.len() - rest.len();
+    let expected = match &e {
+        ErrMode::Backtrack(e) | ErrMode::Cut(e) => e.context().find_map(|context| match context {
+            StrContext::Expected(value) => Some(value.to_string()),
+            _ => None,
+        }),
+        ErrMode::Incomplete(_) => None,
+    };
+    NginxError::Syntax {
+        column: input[..offset].chars().count() + 1,
+        expected: expected.unwrap_or_else(|| "more input".to_string()),
+    }
+}
+
+fn parse_nginx_log(input: &mut &str) -> PResult<NginxLog> {
+    let addr = parse_ip(input)?;
+    parse_ignore(input)?;
+    parse_ignore(input)?;
+    let datetime = parse_datetime(input)?;
+    let (method, url, protocol) = parse_http(input)?;
+    let status = parse_status(input)?;
+    let body_bytes = parse_body_bytes(input)?;
+    let referer = parse_quoted_string(input)?;
+    separator(input)?;
+    let user_agent = parse_quoted_string(input)?;
+    eof.context(description("end of line")).parse_next(input)?;
+
+    Ok(NginxLog {
+        addr,
+        datetime,
+        method,
+        url,
+        protocol,
+        status,
+        body_bytes,
+        referer,
+        user_agent,
+    })
+}
+
+fn description(s: &'static str) -> StrContext {
+    StrContext::Expected(StrContextValue::Description(s))
+}
+
+fn separator(s: &mut &str) -> PResult<()> {
+    take_while(1.., char::is_whitespace)
+        .void()
+        .context(description("whitespace"))
+        .parse_next(s)
+}
+
+/// A field that is not used, such as the `-` for the remote user.
+fn parse_ignore(s: &mut &str) -> PResult<()> {
+    take_till(1.., char::is_whitespace)
+        .void()
+        .context(description("field"))
+        .parse_next(s)?;
+    separator(s)
+}
+
+fn parse_ip(s: &mut &str) -> PResult<IpAddr> {
+    let ret = take_till(1.., char::is_whitespace)
+        .parse_to()
+        .context(description("IP address"))
+        .parse_next(s)?;
+    separator(s)?;
+    Ok(ret)
+}
+
+fn parse_datetime(s: &mut &str) -> PResult<DateTime<Utc>> {
+    let ret = delimited('[', take_until(1.., ']'), ']')
+        .try_map(super::parse_datetime)
+        .context(description("datetime"))
+        .parse_next(s)?;
+    separator(s)?;
+    Ok(ret)
+}
+
+fn parse_http(s: &mut &str) -> PResult<(HttpMethod, String, HttpProto)> {
+    let parser = (parse_method, parse_url, parse_protocol);
+    let quote = '"'.context(StrContext::Expected('"'.into()));
+    let ret = delimited('"'.context(description("request")), parser, quote).parse_next(s)?;
+    separator(s)?;
+    Ok(ret)
+}
+
+fn parse_method(s: &mut &str) -> PResult<HttpMethod> {
+    let ret = alt((
+        "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+    ))
+    .parse_to()
+    .context(description("HTTP method"))
+    .parse_next(s)?;
+    separator(s)?;
+    Ok(ret)
+}
+
+fn parse_url(s: &mut &str) -> PResult<String> {
+    let ret = take_till(1.., char::is_whitespace)
+        .context(description("URL"))
+        .parse_next(s)?;
+    separator(s)?;
+    Ok(ret.to_string())
+}
+
+fn parse_protocol(s: &mut &str) -> PResult<HttpProto> {
+    alt(("HTTP/1.0", "HTTP/1.1", "HTTP/2.0", "HTTP/3.0"))
+        .parse_to()
+        .context(description("HTTP protocol"))
+        .parse_next(s)
+}
+
+fn parse_status(s: &mut &str) -> PResult<u16> {
+    let ret = digit1
+        .parse_to()
+        .context(description("status"))
+        .parse_next(s)?;
+    separator(s)?;
+    Ok(ret)
+}
+
+fn parse_body_bytes(s: &mut &str) -> PResult<u64> {
+    let ret = digit1
+        .parse_to()
+        .context(description("body bytes"))
+        .parse_next(s)?;
+    separator(s)?;
+    Ok(ret)
+}
+
+fn parse_quoted_string(s: &mut &str) -> PResult<String> {
+    let ret = delimited('"', take_until(1.., '"'), '"')
+        .context(description("quoted string"))
+        .parse_next(s)?;
+    Ok(ret.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use anyhow::Result;
+    use chrono::TimeZone;
+
+    const LOG: &str = r#"93.180.71.3 - - [17/May/2015:08:05:32 +0000] "GET /downloads/product_1 HTTP/1.1" 304 0 "-" "Debian APT-HTTP/1.3 (0.8.16~exp12ubuntu10.21)""#;
+
+    #[test]
+    fn parse_ip_should_work() -> Result<()> {
+        let mut s = "1.1.1.1 ";
+        let ip = parse_ip(&mut s).unwrap();
+        assert_eq!(s, "");
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)));
+
+        let mut s = "2001:db8::1\t";
+        assert_eq!(parse_ip(&mut s).unwrap(), "2001:db8::1".parse::<IpAddr>()?);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_datetime_should_work() -> Result<()> {
+        let mut s = "[04/Jun/2015:01:06:58 +0000] ";
+        let dt = parse_datetime(&mut s).unwrap();
+        assert_eq!(s, "");
+        assert_eq!(dt, Utc.with_ymd_and_hms(2015, 6, 4, 1, 6, 58).unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid_datetime_should_fail() {
+        let mut s = "[04/Foo/2015:01:06:58 +0000] ";
+        assert!(parse_datetime(&mut s).is_err());
+    }
+
+    #[test]
+    fn parse_nginx_log_should_work() -> Result<()> {
+        let log = parse(LOG)?;
+        assert_eq!(log.addr, IpAddr::V4(Ipv4Addr::new(93, 180, 71, 3)));
+        assert_eq!(log.method, HttpMethod::Get);
+        assert_eq!(log.url, "/downloads/product_1");
+        assert_eq!(log.protocol, HttpProto::HTTP1_1);
+        assert_eq!(log.status, 304);
+        assert_eq!(log.body_bytes, 0);
+        assert_eq!(log.referer, "-");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error_should_report_column_and_expected_field() {
+        for (line, column, expected) in [
+            ("not a log line", 1, "IP address"),
+            (&LOG.replace("[17/May", "[17/Foo"), 17, "datetime"),
+            (&LOG.replace("HTTP/1.1", "HTTP/9"), 72, "HTTP protocol"),
+            (&format!("{} trailing", LOG), 139, "end of line"),
+        ] {
+            match parse(line) {
+                Err(NginxError::Syntax {
+                    column: c,
+                    expected: e,
+                }) => assert_eq!((c, e.as_str()), (column, expected), "{}", line),
+                ret => panic!("{}: expected syntax error, got {:?}", line, ret),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_should_agree_with_regex_backend() {
+        let ipv6 = LOG.replace("93.180.71.3", "2001:db8::1");
+        let tabs = LOG
+            .replace(' ', "\t")
+            .replace("17/May/2015:08:05:32\t", "17/May/2015:08:05:32 ");
+        for line in [
+            LOG.to_string(),
+            ipv6,
+            tabs,
+            LOG.replace("- -", "- frank"),
+            format!("{} trailing", LOG),
+            format!("{} ", LOG),
+            LOG.replace("HTTP/1.1", "HTTP/1.1 "),
+            LOG.replace("93.180.71.3", "93.180.71"),
+            LOG.replace("304", "99999"),
+            String::new(),
+        ] {
+            match (parse(&line), super::super::regex::parse(&line)) {
+                (Ok(log), Ok(expected)) => assert_eq!(log, expected, "{}", line),
+                (Err(_), Err(_)) => {}
+                (ret, expected) => panic!("{}: {:?} != {:?}", line, ret, expected),
+            }
+        }
+        assert_eq!(
+            parse(&LOG.replace("93.180.71.3", "::1"))
+                .unwrap()
+                .addr
+                .to_string(),
+            "::1"
+        );
+    }
+}
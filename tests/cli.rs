@@ -0,0 +1,131 @@
+//! End-to-end runs of the `grammar` binary: arguments in, stdout, stderr and
+//! exit code out.
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn grammar(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_grammar"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("grammar should start");
+    // a command that fails before reading closes the pipe, which is fine
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn json_fmt_should_reformat_with_either_backend() {
+    for backend in ["pest", "winnow"] {
+        let args = [
+            "json",
+            "fmt",
+            "--backend",
+            backend,
+            "-o",
+            "compact",
+            "--sort-keys",
+        ];
+        let output = grammar(&args, r#"{ "b": [1, 2], "a": null }"#);
+        assert!(output.status.success(), "{}", backend);
+        assert_eq!(stdout(&output), "{\"a\":null,\"b\":[1,2]}\n", "{}", backend);
+    }
+
+    let output = grammar(&["json", "fmt", "--dialect", "json5"], "{a: 'x',}");
+    assert_eq!(stdout(&output), "{\n  \"a\": \"x\"\n}\n");
+}
+
+#[test]
+fn json_parse_error_should_render_diagnostic_and_fail() {
+    let output = grammar(&["json", "validate"], "[1 2]");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    let expected = "error: <stdin>: Failed to parse JSON: invalid array: expected one of `,`, `]`, found `2` at line 1, column 4
+  |
+1 | [1 2]
+  |    ^
+";
+    assert_eq!(stderr(&output), expected);
+
+    let output = grammar(&["json", "validate", "--backend", "pest"], "[1 2]");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("expected one of `,`, `]`, found `2` at line 1, column 4"));
+
+    let output = grammar(&["json", "validate"], "[1, 2]");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "<stdin>: valid\n");
+}
+
+#[test]
+fn json_query_should_print_each_result() {
+    let input = r#"{"marks": [90, 80, 95]}"#;
+    let output = grammar(
+        &[
+            "json",
+            "query",
+            "-o",
+            "compact",
+            ".marks[] | select(. > 85)",
+        ],
+        input,
+    );
+    assert_eq!(stdout(&output), "90\n95\n");
+
+    let args = ["json", "query", "--jsonpath", "-o", "compact", "$.marks[0]"];
+    assert_eq!(stdout(&grammar(&args, input)), "90\n");
+
+    let input = r#"[{"n": "a", "v": 1}, {"n": "b", "w": true}]"#;
+    let output = grammar(&["json", "query", "-o", "table", "."], input);
+    assert_eq!(stdout(&output), "n  v  w\n-  -  ----\na  1\nb     true\n");
+}
+
+#[test]
+fn nginx_should_parse_and_summarize_logs() {
+    let log = r#"93.180.71.3 - - [17/May/2015:08:05:32 +0000] "GET /downloads/product_1 HTTP/1.1" 304 0 "-" "Debian APT-HTTP/1.3 (0.8.16~exp12ubuntu10.21)""#;
+    for backend in ["regex", "winnow"] {
+        let output = grammar(&["nginx", "parse", "--backend", backend], log);
+        assert!(output.status.success(), "{}", backend);
+        assert_eq!(
+            stdout(&output),
+            r#"{"addr":"93.180.71.3","datetime":"2015-05-17T08:05:32Z","method":"GET","url":"/downloads/product_1","protocol":"HTTP/1.1","status":304,"body_bytes":0,"referer":"-","user_agent":"Debian APT-HTTP/1.3 (0.8.16~exp12ubuntu10.21)"}
+"#,
+            "{}",
+            backend
+        );
+    }
+
+    let output = grammar(
+        &["nginx", "stats", "-o", "compact", "fixtures/nginx_logs"],
+        "",
+    );
+    assert!(output.status.success());
+    let stats = grammar::json::parse(&stdout(&output)).unwrap();
+    assert_eq!(stats.pointer("/requests").unwrap().to_string(), "10");
+    assert_eq!(stats.pointer("/status/304").unwrap().to_string(), "6");
+
+    let input = format!("{}\nnot a log line\n", log);
+    let output = grammar(&["nginx", "stats", "-o", "compact"], &input);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error: <stdin>:2: "));
+    assert!(stdout(&output).contains(r#""requests":1,"failed":1"#));
+}
+
+#[test]
+fn invalid_backend_should_be_a_usage_error() {
+    let output = grammar(&["json", "fmt", "--backend", "regex"], "{}");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("[possible values: pest, winnow]"));
+}
@@ -0,0 +1,331 @@
+//! Differential run of every JSON backend over the same inputs: the vendored
+//! corpus in `fixtures/json_test_suite` plus hand-written documents, under
+//! several parse options. Backends must agree on whether an input is valid
+//! and, when it is, produce equal values.
+//!
+//! A corpus case listed in `known_failures.txt` for only some backends, or in
+//! [`KNOWN_DISAGREEMENTS`], is a known disagreement and only reported with
+//! `--nocapture`; one that stops disagreeing fails the test so the lists stay
+//! up to date.
+
+use std::{collections::BTreeSet, fs, path::Path};
+
+use grammar::json::{
+    self, Dialect, DuplicateKeys, JsonError, JsonValue, ParseLimits, ParseOptions, BACKENDS,
+};
+
+const SUITE_DIR: &str = "fixtures/json_test_suite";
+const KNOWN_FAILURES: &str = "known_failures.txt";
+
+/// Implementation-defined corpus cases the backends decide differently.
+const KNOWN_DISAGREEMENTS: &[&str] = &[];
+
+/// Documents every backend must accept or reject alike, chosen to cover the
+/// corners where the two grammars are written differently.
+const INPUTS: &[&str] = &[
+    r#"{"name": "John Doe", "age": 43, "phones": ["+44 1234567", "+44 2345678"]}"#,
+    r#"[0, -0, 1.5, -1.5e-3, 1E+2, 9007199254740993, 18446744073709551616, 1e400]"#,
+    r#"["", "\"\\\/\b\f\n\r\t", "é中", "😀", "plain text"]"#,
+    r#"{"a": {"b": {"c": [[[null, true, false]]]}}}"#,
+    " \n[ 1 ,\t2 ]\r\n",
+    "[]",
+    " {} ",
+    r#"[{}, [], {"a": {}, "b": []}]"#,
+    r#""x""#,
+    " 42 ",
+    "null",
+    "",
+    "[] []",
+    "{} x",
+    "1 2",
+    r#"" a""#,
+    "/* head */ 'x' // tail",
+    "[/**/]",
+    r#"{"a": 1, "b": 2, "a": 3}"#,
+    r#"[1, 2"#,
+    r#"[1, ]"#,
+    r#"[1 2]"#,
+    r#"[01]"#,
+    r#"[1.]"#,
+    r#"[.5]"#,
+    r#"[+1]"#,
+    r#"[NaN]"#,
+    r#"{"a" 1}"#,
+    r#"{"a": 1,}"#,
+    r#"{a: 1}"#,
+    r#"['a']"#,
+    r#"["\x"]"#,
+    r#"["\ud83d"]"#,
+    "[\"tab\there\"]",
+    r#"[1] // comment"#,
+    "// head\n{/* a */ \"a\": [1, /* b */ 2,], // tail\n}",
+    "[1, /* open",
+    "[1, / 2]",
+    "{a: 'x', _b$: \"y\", 'c': 0x1F, d: -0XfF}",
+    "['it\\'s', \"it\\'s\"]",
+    "['a\"b', '\\u00e9', 'tab\there']",
+    "{1a: 1}",
+    "[Infinity, -Infinity, +Infinity, -NaN, +NaN]",
+    "[+1, +0x1F, +1.5e3, +0]",
+    "[+-1]",
+    "[+ 1]",
+    "[-+1]",
+    "+1",
+    "[0x, 0x1.5, 0x7fffffffffffffff, 0x8000000000000000]",
+    "[1,,]",
+    "[,]",
+    "{,}",
+    r#"[[["abcde"]]]"#,
+    r#"{"abcd": [1, 2, 3], "b": {"c": "d"}}"#,
+    r#"{"a": 1, "b": 2, "c": 3}"#,
+    "[\"[[[[\", '[[[[', /* [[[[ */ 1] // [[[[",
+    "{abcde: 1, 'é': [1, 2, 3, 4,]}",
+];
+
+fn options() -> Vec<ParseOptions> {
+    let mut all = vec![ParseOptions::default()];
+    for duplicate_keys in [DuplicateKeys::FirstWins, DuplicateKeys::Error] {
+        all.push(ParseOptions {
+            duplicate_keys,
+            ..Default::default()
+        });
+    }
+    all.push(ParseOptions {
+        lossless_numbers: true,
+        ..Default::default()
+    });
+    let single = [
+        Dialect {
+            comments: true,
+            ..Default::default()
+        },
+        Dialect {
+            trailing_commas: true,
+            ..Default::default()
+        },
+        Dialect {
+            single_quotes: true,
+            ..Default::default()
+        },
+        Dialect {
+            unquoted_keys: true,
+            ..Default::default()
+        },
+        Dialect {
+            hex_numbers: true,
+            ..Default::default()
+        },
+        Dialect {
+            non_finite_numbers: true,
+            ..Default::default()
+        },
+        Dialect {
+            plus_signs: true,
+            ..Default::default()
+        },
+    ];
+    for dialect in single.into_iter().chain([Dialect::JSONC, Dialect::JSON5]) {
+        all.push(ParseOptions {
+            dialect,
+            ..Default::default()
+        });
+    }
+    all.push(ParseOptions {
+        dialect: Dialect::JSON5,
+        lossless_numbers: true,
+        ..Default::default()
+    });
+    let limits = ParseLimits {
+        max_depth: 3,
+        max_string_len: 4,
+        max_array_len: 3,
+        max_object_len: 2,
+        max_input_len: 64,
+    };
+    for dialect in [Dialect::STRICT, Dialect::JSON5] {
+        all.push(ParseOptions {
+            dialect,
+            limits,
+            ..Default::default()
+        });
+    }
+    all
+}
+
+#[test]
+fn backends_should_agree_on_inputs() {
+    let mut mismatches = Vec::new();
+    for input in INPUTS {
+        for options in options() {
+            if let Err(e) = compare(input, &options) {
+                mismatches.push(format!("{:?} with {:?}:\n    {}", input, options, e));
+            }
+        }
+    }
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n  "));
+}
+
+#[test]
+fn backends_should_agree_on_test_suite() {
+    // deeply nested cases stop at the default depth limit
+    if let Err(report) = run_suite() {
+        panic!("{}", report);
+    }
+}
+
+#[test]
+fn written_output_should_parse_alike() {
+    for input in INPUTS {
+        for backend in BACKENDS {
+            let Ok(value) = backend.parse(input) else {
+                continue;
+            };
+            // non-finite doubles have no JSON form
+            let Ok(compact) = json::to_string(&value, &Default::default()) else {
+                continue;
+            };
+            for other in BACKENDS {
+                assert_eq!(
+                    other.parse(&compact).ok().as_ref(),
+                    Some(&value),
+                    "{} output {:?} reparsed with {}",
+                    backend.name(),
+                    compact,
+                    other.name()
+                );
+            }
+        }
+    }
+}
+
+fn run_suite() -> Result<(), String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(SUITE_DIR);
+    let mut known = load_known_disagreements(&dir.join(KNOWN_FAILURES));
+    known.extend(KNOWN_DISAGREEMENTS.iter().map(|name| name.to_string()));
+
+    let mut cases: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    cases.sort();
+
+    let mut mismatches = Vec::new();
+    let mut agreeing = Vec::new();
+    for path in &cases {
+        let name = path.file_name().unwrap().to_str().unwrap();
+        // backends take `&str`, invalid UTF-8 never reaches them
+        let Ok(input) = fs::read_to_string(path) else {
+            continue;
+        };
+        let is_known = known.contains(name);
+        let errors: Vec<_> = options()
+            .iter()
+            .filter_map(|options| compare(&input, options).err().map(|e| (options.clone(), e)))
+            .collect();
+        match errors.first() {
+            None if is_known => agreeing.push(name),
+            None => {}
+            Some((_, e)) if is_known => println!("{} (known): {}", name, e),
+            Some(_) => mismatches.extend(
+                errors
+                    .iter()
+                    .map(|(options, e)| format!("{} with {:?}:\n    {}", name, options, e)),
+            ),
+        }
+    }
+
+    let mut report = String::new();
+    if !mismatches.is_empty() {
+        report.push_str(&format!(
+            "backends disagree:\n  {}\n",
+            mismatches.join("\n  ")
+        ));
+    }
+    if !agreeing.is_empty() {
+        report.push_str(&format!(
+            "backends now agree, remove from the known disagreements:\n  {}\n",
+            agreeing.join("\n  ")
+        ));
+    }
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+/// Parse `input` with every backend and compare each result with the first.
+fn compare(input: &str, options: &ParseOptions) -> Result<(), String> {
+    let results: Vec<_> = BACKENDS
+        .iter()
+        .map(|backend| (backend.name(), backend.parse_with(input, options)))
+        .collect();
+    let (first, expected) = &results[0];
+    for (name, actual) in &results[1..] {
+        if !same(expected, actual) {
+            return Err(format!(
+                "{} gave {}, {} gave {}",
+                first,
+                describe(expected),
+                name,
+                describe(actual)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn same(left: &Result<JsonValue, JsonError>, right: &Result<JsonValue, JsonError>) -> bool {
+    match (left, right) {
+        // NaN is never equal to itself, compare the written form instead
+        (Ok(left), Ok(right)) => left == right || format!("{:?}", left) == format!("{:?}", right),
+        // both report the same repeated key at the same place
+        (
+            Err(JsonError::DuplicateKey { key, offset }),
+            Err(JsonError::DuplicateKey {
+                key: other_key,
+                offset: other_offset,
+            }),
+        ) => key == other_key && offset == other_offset,
+        (Err(JsonError::DuplicateKey { .. }), Err(_))
+        | (Err(_), Err(JsonError::DuplicateKey { .. })) => false,
+        // pest checks the depth before parsing, so it may report a limit
+        // where winnow finds a syntax error first; two limits must match
+        (
+            Err(left @ JsonError::LimitExceeded { .. }),
+            Err(right @ JsonError::LimitExceeded { .. }),
+        ) => left.to_string() == right.to_string(),
+        (Err(_), Err(_)) => true,
+        _ => false,
+    }
+}
+
+fn describe(result: &Result<JsonValue, JsonError>) -> String {
+    match result {
+        Ok(value) => format!("Ok({})", value),
+        Err(e) => format!("Err({})", e.to_string().lines().next().unwrap_or_default()),
+    }
+}
+
+/// Corpus cases that `known_failures.txt` lists for some backends but not
+/// all of them, i.e. where the backends are known to disagree.
+fn load_known_disagreements(path: &Path) -> BTreeSet<String> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let listed: Vec<_> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .map(|(backend, name)| (backend, name.trim()))
+        .collect();
+    listed
+        .iter()
+        .map(|(_, name)| *name)
+        .filter(|name| {
+            let count = listed.iter().filter(|(_, other)| other == name).count();
+            count < BACKENDS.len()
+        })
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,156 @@
+//! RFC 8259 conformance run over the vendored corpus in
+//! `fixtures/json_test_suite`, using the JSONTestSuite naming scheme:
+//! `y_` files must be accepted, `n_` files rejected, and `i_` files are
+//! implementation defined, so either outcome is fine.
+//!
+//! Known deviations are listed in `known_failures.txt` next to the corpus.
+//! Any other mismatch fails the test, and so does a listed case that now
+//! passes, so the list has to be kept up to date. Run with `--nocapture` to
+//! see the full matrix.
+
+use std::{collections::BTreeSet, fs, path::Path, thread};
+
+use grammar::json::BACKENDS;
+
+const SUITE_DIR: &str = "fixtures/json_test_suite";
+const KNOWN_FAILURES: &str = "known_failures.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Accept,
+    Reject,
+    Either,
+}
+
+impl Expect {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.split('_').next()? {
+            "y" => Some(Expect::Accept),
+            "n" => Some(Expect::Reject),
+            "i" => Some(Expect::Either),
+            _ => None,
+        }
+    }
+
+    fn matches(self, accepted: bool) -> bool {
+        match self {
+            Expect::Accept => accepted,
+            Expect::Reject => !accepted,
+            Expect::Either => true,
+        }
+    }
+}
+
+#[test]
+fn json_test_suite_should_match_known_results() {
+    // deeply nested cases recurse once per level, give them room in debug builds
+    let report = thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(run_suite)
+        .unwrap()
+        .join()
+        .unwrap();
+    if let Err(report) = report {
+        panic!("{}", report);
+    }
+}
+
+fn run_suite() -> Result<(), String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(SUITE_DIR);
+    let known = load_known_failures(&dir.join(KNOWN_FAILURES));
+
+    let mut cases: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    cases.sort();
+    assert!(
+        !cases.is_empty(),
+        "no test cases found in {}",
+        dir.display()
+    );
+
+    let mut regressions = Vec::new();
+    let mut fixed = Vec::new();
+    let mut passed = [0; BACKENDS.len()];
+
+    let mut header = format!("{:<56} {:<7}", "case", "expect");
+    for backend in BACKENDS {
+        header.push_str(&format!(" {:<7}", backend.name()));
+    }
+    println!("{}", header);
+    for path in &cases {
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let expect = Expect::from_name(name)
+            .unwrap_or_else(|| panic!("{}: name must start with y_, n_ or i_", name));
+        let bytes = fs::read(path).unwrap();
+
+        let mut row = format!("{:<56} {:<7}", name, format!("{:?}", expect));
+        for (i, backend) in BACKENDS.iter().enumerate() {
+            // backends take `&str`, invalid UTF-8 never reaches them
+            let accepted = std::str::from_utf8(&bytes).is_ok_and(|s| backend.parse(s).is_ok());
+            let ok = expect.matches(accepted);
+            let backend = backend.name();
+            let is_known = known.contains(&(backend.to_string(), name.to_string()));
+            match (ok, is_known) {
+                (true, false) => passed[i] += 1,
+                (false, false) => regressions.push(format!("{} {}", backend, name)),
+                (true, true) => fixed.push(format!("{} {}", backend, name)),
+                (false, true) => {}
+            }
+            let cell = match (accepted, ok) {
+                (true, true) => "ok",
+                (false, true) => "err",
+                (true, false) => "ok!",
+                (false, false) => "err!",
+            };
+            row.push_str(&format!(" {:<7}", cell));
+        }
+        println!("{}", row);
+    }
+    for (i, backend) in BACKENDS.iter().enumerate() {
+        println!(
+            "{}: {}/{} as expected",
+            backend.name(),
+            passed[i],
+            cases.len()
+        );
+    }
+
+    let mut report = String::new();
+    if !regressions.is_empty() {
+        report.push_str(&format!(
+            "unexpected results (`!` in the matrix):\n  {}\n",
+            regressions.join("\n  ")
+        ));
+    }
+    if !fixed.is_empty() {
+        report.push_str(&format!(
+            "now passing, remove from {}:\n  {}\n",
+            KNOWN_FAILURES,
+            fixed.join("\n  ")
+        ));
+    }
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+/// Lines of `<backend> <case>`; blank lines and `#` comments are ignored.
+fn load_known_failures(path: &Path) -> BTreeSet<(String, String)> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (backend, name) = line
+                .split_once(char::is_whitespace)
+                .unwrap_or_else(|| panic!("malformed line in {}: {}", KNOWN_FAILURES, line));
+            (backend.to_string(), name.trim().to_string())
+        })
+        .collect()
+}